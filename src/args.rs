@@ -1,24 +1,84 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use tracing_subscriber::fmt::time::ChronoLocal;
 
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, args_conflicts_with_subcommands = true)]
 pub struct Args {
-    pub json_path: String,
+    #[command(flatten)]
+    pub spec: Option<SpecArgs>,
+
+    /// Run `cargo fmt` and `cargo clippy --fix` on the generated crate
+    #[arg(long)]
+    pub clippy: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Exit with an error and a diff when OUTPUT_DIR differs from the crate
+    /// the spec generates, without writing to it
+    Check(SpecArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SpecArgs {
+    /// OpenAPI spec, JSON or YAML
+    pub spec_path: String,
 
     pub output_dir: Option<String>,
+
+    /// TOML file mapping schema names, formats or JSON pointers to Rust types
+    #[arg(long)]
+    pub type_mapping: Option<String>,
+
+    /// Exit with an error when any operation or schema was skipped
+    #[arg(long)]
+    pub strict: bool,
 }
 
 impl Args {
+    pub fn init_tracing(&self) {
+
+        tracing_subscriber::fmt()
+            .with_timer(ChronoLocal::rfc_3339())
+            .init();
+    }
+}
+
+impl SpecArgs {
     pub fn output_path(&self) -> &str {
 
         self.output_dir.as_deref().unwrap_or("./client")
     }
+}
 
-    pub fn init_tracing(&self) {
+#[cfg(test)]
+mod tests {
 
-        tracing_subscriber::fmt()
-            .with_timer(ChronoLocal::rfc_3339())
-            .init();
+    use clap::Parser;
+
+    use super::{Args, Command};
+
+    #[test]
+
+    fn test_strict() {
+
+        let args = Args::try_parse_from(["falocon", "--strict", "spec.json"]).unwrap();
+
+        assert!(args.spec.unwrap().strict);
+
+        let args = Args::try_parse_from(["falocon", "check", "--strict", "spec.json", "./client"])
+            .unwrap();
+
+        let Some(Command::Check(spec)) = args.command else {
+
+            panic!("expected the check command");
+        };
+
+        assert!(spec.strict);
+
+        assert_eq!(spec.output_path(), "./client");
     }
 }
@@ -0,0 +1,38 @@
+use crate::{Diagnostic, FileDiff};
+
+/// Errors returned by the public API.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Could not read {path}: {source}")]
+    Read {
+        path: String,
+        source: std::io::Error,
+    },
+    /// The spec is not a valid JSON or YAML OpenAPI document.
+    #[error("Invalid spec: {0}")]
+    Spec(String),
+    /// A reference to another file could not be resolved.
+    #[error("Invalid reference: {0}")]
+    Reference(String),
+    #[error("Invalid type mapping: {0}")]
+    TypeMapping(String),
+    #[error("Could not write the crate: {0}")]
+    Write(#[from] std::io::Error),
+    #[error("Could not format the crate: {0}")]
+    Format(String),
+    /// Operations or schemas were skipped in strict mode.
+    #[error("{} operations or schemas were skipped", .0.len())]
+    Skipped(Vec<Diagnostic>),
+    /// No output directory was given and `OUT_DIR` is not set, outside of a
+    /// build script.
+    #[error("OUT_DIR is not set, call Builder::out_dir outside of build scripts")]
+    OutDir,
+    /// A `mod` declaration names a file the generated crate does not contain.
+    #[error("Module {0} was not generated")]
+    MissingModule(String),
+    /// The crate on disk differs from the generated one, in check mode.
+    #[error("{} generated files are out of date", .0.len())]
+    OutOfDate(Vec<FileDiff>),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,189 @@
+use heck::ToSnakeCase;
+use openapiv3::{ObjectType, ReferenceOr, Schema, SchemaKind, Type};
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::collections::HashSet;
+
+use crate::generator::{
+    context::Context,
+    create_rust_safe_ident,
+    fields::{FieldsGenerator, field_names},
+};
+
+pub struct AllOfGenerator<'a> {
+    ctx: &'a Context,
+    struct_name: &'a str,
+    parts: &'a [ReferenceOr<Schema>],
+}
+
+impl<'a> AllOfGenerator<'a> {
+    pub fn new(ctx: &'a Context, struct_name: &'a str, parts: &'a [ReferenceOr<Schema>]) -> Self {
+
+        Self {
+            ctx,
+            struct_name,
+            parts,
+        }
+    }
+
+    /// Generates the fields of the composed struct. Referenced parts become
+    /// `#[serde(flatten)]` fields, the properties of inline parts are merged
+    /// in directly.
+    pub fn generate(&self) -> Result<TokenStream, String> {
+
+        let mut fields = TokenStream::new();
+
+        // every field name of the struct, for the additional properties
+        // fields to avoid
+        let mut taken = HashSet::new();
+
+        for part in self.parts {
+
+            match part {
+                ReferenceOr::Reference { reference } => {
+
+                    let type_name = self.ctx.schema_reference(reference)?;
+
+                    taken.insert(create_rust_safe_ident(&type_name.to_snake_case()).to_string());
+                }
+                ReferenceOr::Item(schema) => {
+                    if let Some(obj) = object_type(schema) {
+
+                        taken.extend(field_names(&obj));
+                    }
+                }
+            }
+        }
+
+        for part in self.parts {
+
+            match part {
+                ReferenceOr::Reference { reference } => {
+
+                    let type_name = self.ctx.schema_reference(reference)?;
+
+                    let field_ident = create_rust_safe_ident(&type_name.to_snake_case());
+
+                    let type_ident = self.ctx.schema_type(type_name)?;
+
+                    fields.extend(quote! {
+                        #[serde(flatten)]
+                        pub #field_ident: #type_ident,
+                    });
+                }
+                // e.g. a part only narrowing `required`
+                ReferenceOr::Item(Schema {
+                    schema_kind: SchemaKind::Any(any),
+                    ..
+                }) if any.typ.is_none() && any.properties.is_empty() => {}
+                ReferenceOr::Item(schema) => {
+
+                    let Some(obj) = object_type(schema) else {
+
+                        return Err("Unsupported allOf part, expected an object".to_string());
+                    };
+
+                    fields.extend(
+                        FieldsGenerator::new(self.ctx, self.struct_name, &obj)
+                            .generate_taken(&mut taken)?,
+                    );
+                }
+            }
+        }
+
+        Ok(fields)
+    }
+}
+
+/// Object view of a schema. Parts of an `allOf` frequently omit
+/// `type: object` and only declare `properties`.
+pub fn object_type(schema: &Schema) -> Option<ObjectType> {
+
+    match &schema.schema_kind {
+        SchemaKind::Type(Type::Object(obj)) => Some(obj.clone()),
+        SchemaKind::Any(any)
+            if any.typ.as_deref() == Some("object")
+                || (any.typ.is_none() && !any.properties.is_empty()) =>
+        {
+            Some(ObjectType {
+                properties: any.properties.clone(),
+                required: any.required.clone(),
+                additional_properties: any.additional_properties.clone(),
+                min_properties: any.min_properties,
+                max_properties: any.max_properties,
+            })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    #[test]
+
+    fn test_all_of() {
+
+        let openapi: openapiv3::OpenAPI = serde_json::from_str(
+            r##"{
+                "openapi": "3.0.3",
+                "info": { "title": "t", "version": "1" },
+                "paths": {},
+                "components": { "schemas": { "PetBase": { "type": "object" } } }
+            }"##,
+        )
+        .unwrap();
+
+        let parts: Vec<openapiv3::ReferenceOr<openapiv3::Schema>> = serde_json::from_str(
+            r##"[
+                { "$ref": "#/components/schemas/PetBase" },
+                { "required": ["name"] },
+                { "properties": { "name": { "type": "string" }, "tag": { "type": "string" } }, "required": ["name"] }
+            ]"##,
+        )
+        .unwrap();
+
+        let ctx = crate::Context::new().with_spec(&openapi);
+
+        let fields = super::AllOfGenerator::new(&ctx, "Dog", &parts)
+            .generate()
+            .unwrap()
+            .to_string();
+
+        assert_eq!(
+            fields,
+            "# [serde (flatten)] pub pet_base : PetBase , pub name : String , pub tag : Option < String > ,"
+        );
+    }
+
+    #[test]
+
+    fn test_additional_properties_names() {
+
+        let parts: Vec<openapiv3::ReferenceOr<openapiv3::Schema>> = serde_json::from_str(
+            r##"[
+                {
+                    "properties": { "additional_properties": { "type": "string" } },
+                    "additionalProperties": { "type": "integer" }
+                },
+                { "properties": { "tag": { "type": "string" } }, "additionalProperties": true }
+            ]"##,
+        )
+        .unwrap();
+
+        let ctx = crate::Context::new();
+
+        let fields = super::AllOfGenerator::new(&ctx, "Dog", &parts)
+            .generate()
+            .unwrap()
+            .to_string();
+
+        assert_eq!(
+            fields,
+            "pub additional_properties : Option < String > , \
+             # [serde (flatten)] pub additional_properties2 : HashMap < String , i32 > , \
+             pub tag : Option < String > , \
+             # [serde (flatten)] pub additional_properties3 : HashMap < String , serde_json :: Value > ,"
+        );
+    }
+}
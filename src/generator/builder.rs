@@ -0,0 +1,165 @@
+use std::path::{Path, PathBuf};
+
+use proc_macro2::TokenStream;
+
+use crate::{
+    CrateWriter, Dependency, Diagnostic, Error, FileDiff, MemoryOutput, OpenAPIGenerator,
+    OperationMap, Rename, Result, TypeMapping,
+};
+
+/// Entry point of the library API:
+///
+/// ```no_run
+/// let generated = falocon::Generator::builder()
+///     .spec("openapi.yaml")
+///     .generate()?;
+///
+/// generated.write("./client")?;
+/// # Ok::<(), falocon::Error>(())
+/// ```
+pub struct Generator;
+
+impl Generator {
+    pub fn builder() -> GeneratorBuilder {
+
+        GeneratorBuilder::default()
+    }
+}
+
+enum Spec {
+    Path(PathBuf),
+    Data(String),
+}
+
+#[derive(Default)]
+pub struct GeneratorBuilder {
+    spec: Option<Spec>,
+    type_mapping: Option<TypeMapping>,
+    strict: bool,
+}
+
+impl GeneratorBuilder {
+    /// Spec file to read, JSON or YAML. References to other files are
+    /// relative to it.
+    pub fn spec(mut self, path: impl Into<PathBuf>) -> Self {
+
+        self.spec = Some(Spec::Path(path.into()));
+
+        self
+    }
+
+    /// Spec content, JSON or YAML, for specs that are not stored in a file.
+    pub fn spec_data(mut self, data: impl Into<String>) -> Self {
+
+        self.spec = Some(Spec::Data(data.into()));
+
+        self
+    }
+
+    pub fn type_mapping(mut self, type_mapping: TypeMapping) -> Self {
+
+        self.type_mapping = Some(type_mapping);
+
+        self
+    }
+
+    /// Fails the generation with [`Error::Skipped`] when any operation or
+    /// schema is skipped.
+    pub fn strict(mut self, strict: bool) -> Self {
+
+        self.strict = strict;
+
+        self
+    }
+
+    pub fn generate(self) -> Result<GeneratedCrate> {
+
+        let mut sources = Vec::new();
+
+        let mut generator = match self.spec {
+            Some(Spec::Path(path)) => {
+
+                let data = std::fs::read_to_string(&path).map_err(|source| Error::Read {
+                    path: path.display().to_string(),
+                    source,
+                })?;
+
+                let generator = OpenAPIGenerator::from_spec(&path.to_string_lossy(), &data)?;
+
+                sources.push(path);
+
+                generator
+            }
+            Some(Spec::Data(data)) => OpenAPIGenerator::from_spec("", &data)?,
+            None => return Err(Error::Spec("no spec given".to_string())),
+        };
+
+        sources.extend_from_slice(generator.sources());
+
+        if let Some(type_mapping) = self.type_mapping {
+
+            generator = generator.with_type_mapping(type_mapping)?;
+        }
+
+        let methods = generator.gen_methods();
+
+        let types = generator.gen_types();
+
+        let diagnostics = generator.diagnostics();
+
+        if self.strict && !diagnostics.is_empty() {
+
+            return Err(Error::Skipped(diagnostics));
+        }
+
+        Ok(GeneratedCrate {
+            types,
+            methods,
+            dependencies: generator.dependencies(),
+            diagnostics,
+            renames: generator.renames(),
+            sources,
+        })
+    }
+}
+
+/// Code generated from a spec, ready to be written as a crate.
+pub struct GeneratedCrate {
+    pub types: TokenStream,
+    pub methods: OperationMap,
+    pub dependencies: Vec<Dependency>,
+    /// Operations and schemas that were skipped.
+    pub diagnostics: Vec<Diagnostic>,
+    /// Names changed to avoid clashes.
+    pub renames: Vec<Rename>,
+    /// Files the spec was read from, the spec itself and the files its
+    /// references point to.
+    pub sources: Vec<PathBuf>,
+}
+
+impl GeneratedCrate {
+    /// Writes the crate into `output_dir`, formatted.
+    pub fn write(&self, output_dir: impl AsRef<Path>) -> Result<()> {
+
+        let output_dir = output_dir.as_ref().to_string_lossy();
+
+        CrateWriter::new(&output_dir, self.types.clone(), self.methods.clone())
+            .with_dependencies(self.dependencies.clone())
+            .write()?;
+
+        Ok(())
+    }
+
+    /// Generates the crate in memory and compares it with the one in
+    /// `output_dir`, without modifying it. Returns the files that differ.
+    pub fn check(&self, output_dir: impl AsRef<Path>) -> Result<Vec<FileDiff>> {
+
+        let mut output = MemoryOutput::over(output_dir.as_ref());
+
+        CrateWriter::new("", self.types.clone(), self.methods.clone())
+            .with_dependencies(self.dependencies.clone())
+            .write_to(&mut output)?;
+
+        Ok(output.diff()?)
+    }
+}
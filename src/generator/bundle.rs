@@ -0,0 +1,380 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use heck::ToPascalCase;
+use serde_json::{Map, Value};
+
+use crate::{DATA_KEYS, NAME_MAPS};
+
+/// Keys whose values are schemas, so that external references found below
+/// them are merged as component schemas rather than inlined.
+const SCHEMA_KEYS: [&str; 12] = [
+    "schema",
+    "schemas",
+    "definitions",
+    "$defs",
+    "items",
+    "properties",
+    "patternProperties",
+    "additionalProperties",
+    "allOf",
+    "oneOf",
+    "anyOf",
+    "not",
+];
+
+/// Parses a JSON or YAML document, picked from the extension of `path`, or
+/// from the content itself: JSON documents start with `{`.
+pub fn parse_document(path: &Path, data: &str) -> Result<Value, String> {
+
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_lowercase);
+
+    let json = match extension.as_deref() {
+        Some("json") => true,
+        Some("yaml" | "yml") => false,
+        _ => data.trim_start().starts_with('{'),
+    };
+
+    if json {
+
+        serde_json::from_str(data).map_err(|e| format!("{}: {e}", path.display()))
+    } else {
+
+        serde_yaml::from_str(data).map_err(|e| format!("{}: {e}", path.display()))
+    }
+}
+
+/// Replaces the references to other files (`./schemas/user.yaml#/User`) of
+/// the spec at `path` with local ones.
+///
+/// Referenced schemas are merged into the spec's component schemas, named
+/// after the last segment of their pointer or after their file, with a
+/// numeric suffix when the name is taken. Other referenced objects, such as
+/// parameters or responses, are inlined. References inside the loaded files
+/// are rebased on those files. Returns the files that were loaded.
+pub fn bundle_external_refs(spec: &mut Value, path: &Path) -> Result<Vec<PathBuf>, String> {
+
+    let swagger = spec.get("swagger").is_some();
+
+    let (namespace, prefix) = if swagger {
+
+        (vec!["definitions"], "#/definitions/")
+    } else {
+
+        (vec!["components", "schemas"], "#/components/schemas/")
+    };
+
+    let root = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+    let mut bundler = Bundler {
+        root: root.clone(),
+        prefix,
+        documents: HashMap::new(),
+        names: HashMap::new(),
+        taken: HashSet::new(),
+        schemas: Vec::new(),
+    };
+
+    let mut adopted = Vec::new();
+
+    if let Some(Value::Object(schemas)) = namespace
+        .iter()
+        .try_fold(&*spec, |value, key| value.get(key))
+    {
+
+        bundler.taken.extend(schemas.keys().cloned());
+
+        // a component that only points to another file takes the name of
+        // the component
+        for (name, schema) in schemas {
+
+            if let Some(reference) = schema.get("$ref").and_then(Value::as_str)
+                && !reference.starts_with('#')
+            {
+
+                let key = bundler.locate(&root, reference)?;
+
+                bundler.names.insert(key.clone(), name.clone());
+
+                adopted.push((name.clone(), key));
+            }
+        }
+    }
+
+    for (name, (file, pointer)) in adopted {
+
+        let mut schema = bundler.load(&file, &pointer)?;
+
+        bundler.walk(&mut schema, &file, true, false)?;
+
+        schemas_mut(spec, &namespace)?.insert(name, schema);
+    }
+
+    bundler.walk(spec, &root, false, false)?;
+
+    if !bundler.schemas.is_empty() {
+
+        schemas_mut(spec, &namespace)?.extend(bundler.schemas);
+    }
+
+    let mut files: Vec<PathBuf> = bundler.documents.into_keys().collect();
+
+    files.sort();
+
+    Ok(files)
+}
+
+/// The schema namespace of the spec, created when missing.
+fn schemas_mut<'a>(
+    spec: &'a mut Value,
+    namespace: &[&str],
+) -> Result<&'a mut Map<String, Value>, String> {
+
+    let mut schemas = spec;
+
+    for key in namespace {
+
+        let Value::Object(object) = schemas else {
+
+            return Err(format!("/{} is not an object", namespace.join("/")));
+        };
+
+        schemas = object
+            .entry(*key)
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+
+    match schemas {
+        Value::Object(schemas) => Ok(schemas),
+        _ => Err(format!("/{} is not an object", namespace.join("/"))),
+    }
+}
+
+struct Bundler {
+    root: PathBuf,
+    prefix: &'static str,
+    documents: HashMap<PathBuf, Value>,
+    /// Merged schema names by file and pointer, so that each is merged once.
+    names: HashMap<(PathBuf, String), String>,
+    taken: HashSet<String>,
+    schemas: Vec<(String, Value)>,
+}
+
+impl Bundler {
+    /// `base` is the file `value` comes from, which its references are
+    /// relative to. `name_map` is set for the values of [`NAME_MAPS`], whose
+    /// keys are names rather than keywords.
+    fn walk(
+        &mut self,
+        value: &mut Value,
+        base: &Path,
+        in_schema: bool,
+        name_map: bool,
+    ) -> Result<(), String> {
+
+        match value {
+            Value::Array(items) => {
+                for item in items {
+
+                    self.walk(item, base, in_schema, false)?;
+                }
+            }
+            Value::Object(object) if name_map => {
+                for value in object.values_mut() {
+
+                    self.walk(value, base, in_schema, false)?;
+                }
+            }
+            Value::Object(object) => {
+
+                if let Some(Value::String(reference)) = object.get("$ref")
+                    && (!reference.starts_with('#') || base != self.root)
+                {
+
+                    let reference = reference.clone();
+
+                    let (file, pointer) = self.locate(base, &reference)?;
+
+                    if file == self.root {
+
+                        object.insert("$ref".to_string(), format!("#{pointer}").into());
+                    } else if in_schema {
+
+                        let name = self.merge_schema(file, pointer)?;
+
+                        object.insert("$ref".to_string(), format!("{}{name}", self.prefix).into());
+                    } else {
+
+                        let mut target = self.load(&file, &pointer)?;
+
+                        self.walk(&mut target, &file, false, false)?;
+
+                        *value = target;
+                    }
+
+                    return Ok(());
+                }
+
+                for (key, value) in object.iter_mut() {
+
+                    if DATA_KEYS.contains(&key.as_str()) {
+
+                        continue;
+                    }
+
+                    let in_schema = in_schema || SCHEMA_KEYS.contains(&key.as_str());
+
+                    self.walk(value, base, in_schema, NAME_MAPS.contains(&key.as_str()))?;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// The absolute file and JSON pointer a reference found in `base` points
+    /// to.
+    fn locate(&self, base: &Path, reference: &str) -> Result<(PathBuf, String), String> {
+
+        if reference.starts_with("http://") || reference.starts_with("https://") {
+
+            return Err(format!("Remote reference {reference} is not supported"));
+        }
+
+        let (file, pointer) = reference.split_once('#').unwrap_or((reference, ""));
+
+        let file = if file.is_empty() {
+
+            base.to_path_buf()
+        } else {
+
+            base.parent()
+                .unwrap_or(Path::new("."))
+                .join(file)
+                .canonicalize()
+                .map_err(|e| format!("Could not resolve reference {reference}: {e}"))?
+        };
+
+        Ok((file, pointer.to_string()))
+    }
+
+    fn load(&mut self, file: &Path, pointer: &str) -> Result<Value, String> {
+
+        if !self.documents.contains_key(file) {
+
+            let data = std::fs::read_to_string(file)
+                .map_err(|e| format!("Could not read {}: {e}", file.display()))?;
+
+            let document = parse_document(file, &data)?;
+
+            self.documents.insert(file.to_path_buf(), document);
+        }
+
+        self.documents[file]
+            .pointer(pointer)
+            .cloned()
+            .ok_or_else(|| format!("{}#{pointer} does not exist", file.display()))
+    }
+
+    /// Merges the schema at `pointer` in `file` and returns its name.
+    fn merge_schema(&mut self, file: PathBuf, pointer: String) -> Result<String, String> {
+
+        let key = (file, pointer);
+
+        if let Some(name) = self.names.get(&key) {
+
+            return Ok(name.clone());
+        }
+
+        let (file, pointer) = &key;
+
+        let base_name = match pointer.rsplit('/').next() {
+            Some(segment) if !segment.is_empty() => segment.replace("~1", "/").replace("~0", "~"),
+            _ => file
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or("Schema")
+                .to_pascal_case(),
+        };
+
+        let mut name = base_name.clone();
+
+        let mut suffix = 2;
+
+        while self.taken.contains(&name) {
+
+            name = format!("{base_name}{suffix}");
+
+            suffix += 1;
+        }
+
+        self.taken.insert(name.clone());
+
+        // named before walking, so that recursive schemas refer to themselves
+        self.names.insert(key.clone(), name.clone());
+
+        let mut schema = self.load(&key.0, &key.1)?;
+
+        self.walk(&mut schema, &key.0, true, false)?;
+
+        self.schemas.push((name.clone(), schema));
+
+        Ok(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::path::Path;
+
+    use serde_json::json;
+
+    #[test]
+
+    fn test_bundle_external_refs() {
+
+        let path = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/multi-file/openapi.yaml"
+        ));
+
+        let mut spec =
+            super::parse_document(path, &std::fs::read_to_string(path).unwrap()).unwrap();
+
+        super::bundle_external_refs(&mut spec, path).unwrap();
+
+        assert_eq!(
+            spec["components"]["schemas"],
+            json!({
+                "User": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "address": { "$ref": "#/components/schemas/Address2" },
+                        "manager": { "$ref": "#/components/schemas/User" },
+                        "default": { "$ref": "#/components/schemas/Address2" }
+                    },
+                    "example": { "$ref": "./missing.yaml" }
+                },
+                "Address": { "type": "string" },
+                "Address2": { "type": "object", "properties": { "city": { "type": "string" } } }
+            })
+        );
+
+        assert_eq!(
+            spec["paths"]["/users"]["get"]["responses"]["200"]["content"]["application/json"]["schema"]
+                ["items"],
+            json!({ "$ref": "#/components/schemas/User" })
+        );
+
+        assert_eq!(
+            spec["paths"]["/users"]["get"]["parameters"][0],
+            json!({ "name": "limit", "in": "query", "schema": { "type": "integer" } })
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeSet, HashMap},
+};
+
+use heck::ToPascalCase;
+use openapiv3::{Components, OpenAPI, Parameter, ReferenceOr, RequestBody, Response, Schema};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+use crate::generator::{
+    diagnostics::Diagnostic,
+    formats::Dependency,
+    mapping::{TypeMapping, rust_path},
+    naming::{Names, Rename},
+};
+
+/// State shared by the generators while generating one spec.
+#[derive(Default)]
+pub struct Context {
+    type_mapping: TypeMapping,
+    components: Components,
+    names: RefCell<Names>,
+    /// Type name given to each requested nested type name.
+    inline_names: RefCell<HashMap<String, String>>,
+    inline_types: RefCell<TokenStream>,
+    dependencies: RefCell<BTreeSet<Dependency>>,
+    diagnostics: RefCell<Vec<Diagnostic>>,
+}
+
+impl Context {
+    pub fn new() -> Self {
+
+        Self::default()
+    }
+
+    pub fn with_type_mapping(type_mapping: TypeMapping) -> Self {
+
+        Self {
+            type_mapping,
+            ..Self::default()
+        }
+    }
+
+    /// The spec being generated, which references are resolved against and
+    /// operations are named from.
+    pub fn with_spec(mut self, openapi: &OpenAPI) -> Self {
+
+        self.components = openapi.components.clone().unwrap_or_default();
+
+        self.names = RefCell::new(Names::new(openapi));
+
+        self
+    }
+
+    pub fn type_mapping(&self) -> &TypeMapping {
+
+        &self.type_mapping
+    }
+
+    /// Rust type of the component schema `name`, either its generated type
+    /// or the path it is mapped to.
+    pub fn schema_type(&self, name: &str) -> Result<TokenStream, String> {
+
+        if let Some(path) = self.type_mapping.schema(name) {
+
+            return rust_path(path);
+        }
+
+        let type_ident = format_ident!("{}", self.schema_type_name(name));
+
+        Ok(quote! { #type_ident })
+    }
+
+    /// Rust type of the component schema `reference` points to.
+    pub fn reference_type(&self, reference: &str) -> Result<TokenStream, String> {
+
+        self.schema_type(self.schema_reference(reference)?)
+    }
+
+    /// Name of the component schema `reference` points to. Only references
+    /// to the spec's own component schemas are supported.
+    pub fn schema_reference<'r>(&self, reference: &'r str) -> Result<&'r str, String> {
+
+        let Some(name) = reference.strip_prefix("#/components/schemas/") else {
+
+            return Err(format!("Unsupported reference {reference}"));
+        };
+
+        if !self.components.schemas.contains_key(name) && self.type_mapping.schema(name).is_none() {
+
+            return Err(format!("Unresolved reference {reference}"));
+        }
+
+        Ok(name)
+    }
+
+    /// Name of the type generated for the component schema `name`, see
+    /// [`Names`].
+    pub fn schema_type_name(&self, name: &str) -> String {
+
+        self.names
+            .borrow()
+            .schemas
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| name.to_pascal_case())
+    }
+
+    /// snake_case name of the operation at `pointer`, see [`Names`].
+    pub fn operation_name(&self, pointer: &str) -> Option<String> {
+
+        self.names.borrow().operations.get(pointer).cloned()
+    }
+
+    /// Name of the type generated for a nested schema, `name` or a suffixed
+    /// variant of it when taken by another type. The same `name` always
+    /// gets the same type name.
+    pub fn inline_type_name(&self, name: &str) -> String {
+
+        self.inline_names
+            .borrow_mut()
+            .entry(name.to_string())
+            .or_insert_with(|| {
+
+                self.names
+                    .borrow_mut()
+                    .claim_type(None, &name.to_pascal_case())
+            })
+            .clone()
+    }
+
+    /// Generated names changed because of a clash.
+    pub fn renames(&self) -> Vec<Rename> {
+
+        self.names.borrow().renames.clone()
+    }
+
+    pub fn schema<'a>(&'a self, schema: &'a ReferenceOr<Schema>) -> Result<&'a Schema, String> {
+
+        resolve(schema, "#/components/schemas/", |name| {
+
+            self.components.schemas.get(name)
+        })
+    }
+
+    pub fn parameter<'a>(
+        &'a self,
+        parameter: &'a ReferenceOr<Parameter>,
+    ) -> Result<&'a Parameter, String> {
+
+        resolve(parameter, "#/components/parameters/", |name| {
+
+            self.components.parameters.get(name)
+        })
+    }
+
+    pub fn request_body<'a>(
+        &'a self,
+        request_body: &'a ReferenceOr<RequestBody>,
+    ) -> Result<&'a RequestBody, String> {
+
+        resolve(request_body, "#/components/requestBodies/", |name| {
+
+            self.components.request_bodies.get(name)
+        })
+    }
+
+    pub fn response<'a>(
+        &'a self,
+        response: &'a ReferenceOr<Response>,
+    ) -> Result<&'a Response, String> {
+
+        resolve(response, "#/components/responses/", |name| {
+
+            self.components.responses.get(name)
+        })
+    }
+
+    /// Queues the definition of a named inline schema, to be emitted next to
+    /// the type or operation referencing it.
+    pub fn push_inline_type(&self, definition: TokenStream) {
+
+        self.inline_types.borrow_mut().extend(definition);
+    }
+
+    pub fn take_inline_types(&self) -> TokenStream {
+
+        std::mem::take(&mut *self.inline_types.borrow_mut())
+    }
+
+    pub fn require(&self, dependency: Dependency) {
+
+        self.dependencies.borrow_mut().insert(dependency);
+    }
+
+    pub fn dependencies(&self) -> Vec<Dependency> {
+
+        self.dependencies.borrow().iter().copied().collect()
+    }
+
+    /// Records that the part of the spec at `pointer` was skipped.
+    pub fn report(&self, pointer: String, reason: impl Into<String>) {
+
+        self.diagnostics.borrow_mut().push(Diagnostic {
+            pointer,
+            reason: reason.into(),
+        });
+    }
+
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+
+        self.diagnostics.borrow().clone()
+    }
+}
+
+/// Follows `item` through the components found under `prefix`, including
+/// components that are references themselves.
+fn resolve<'a, T>(
+    mut item: &'a ReferenceOr<T>,
+    prefix: &str,
+    lookup: impl Fn(&str) -> Option<&'a ReferenceOr<T>>,
+) -> Result<&'a T, String> {
+
+    // bounds reference cycles
+    for _ in 0..32 {
+
+        match item {
+            ReferenceOr::Item(item) => return Ok(item),
+            ReferenceOr::Reference { reference } => {
+
+                item = reference
+                    .strip_prefix(prefix)
+                    .and_then(&lookup)
+                    .ok_or_else(|| format!("Unresolved reference {reference}"))?;
+            }
+        }
+    }
+
+    Err(format!("Reference cycle through {prefix}"))
+}
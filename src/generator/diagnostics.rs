@@ -0,0 +1,38 @@
+use std::fmt;
+
+/// A part of the spec the generator skipped, located by its JSON pointer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub pointer: String,
+    pub reason: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+
+        write!(f, "#{}: {}", self.pointer, self.reason)
+    }
+}
+
+/// JSON pointer made of `segments`, escaped as RFC 6901 requires.
+pub fn json_pointer(segments: &[&str]) -> String {
+
+    segments
+        .iter()
+        .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    #[test]
+
+    fn test_json_pointer() {
+
+        assert_eq!(
+            super::json_pointer(&["paths", "/items/{Id}", "get"]),
+            "/paths/~1items~1{Id}/get"
+        );
+    }
+}
@@ -1,7 +1,9 @@
 use heck::ToPascalCase;
 use openapiv3::StringType;
 use proc_macro2::TokenStream;
-use quote::{format_ident, quote};
+use quote::quote;
+
+use crate::generator::create_rust_safe_ident;
 
 pub struct EnumsGenerator<'a> {
     schema: &'a StringType,
@@ -21,7 +23,7 @@ impl<'a> EnumsGenerator<'a> {
 
             if let Some(variant_str) = value.as_ref().map(|v| v.as_str()) {
 
-                let variant_name = format_ident!("{}", variant_str.to_pascal_case());
+                let variant_name = create_rust_safe_ident(&variant_str.to_pascal_case());
 
                 variants.extend(quote! {
                     #[serde(rename = #variant_str)]
@@ -1,24 +1,41 @@
 use heck::{ToPascalCase, ToSnakeCase};
-use openapiv3::{ObjectType, ReferenceOr};
+use openapiv3::{AdditionalProperties, ObjectType, ReferenceOr};
 use proc_macro2::{Ident, TokenStream};
 use quote::{format_ident, quote};
 use std::collections::HashSet;
 
-use crate::generator::{docs::DocsGenerator, types::TypesGenerator};
+use crate::generator::{
+    context::Context,
+    docs::DocsGenerator,
+    types::{TypesGenerator, map_type},
+};
 
 pub struct FieldsGenerator<'a> {
+    ctx: &'a Context,
     struct_name: &'a str,
     obj: &'a ObjectType,
 }
 
 impl<'a> FieldsGenerator<'a> {
-    pub fn new(struct_name: &'a str, obj: &'a ObjectType) -> Self {
+    pub fn new(ctx: &'a Context, struct_name: &'a str, obj: &'a ObjectType) -> Self {
 
-        Self { struct_name, obj }
+        Self {
+            ctx,
+            struct_name,
+            obj,
+        }
     }
 
     pub fn generate(&self) -> Result<TokenStream, String> {
 
+        self.generate_taken(&mut field_names(self.obj).collect())
+    }
+
+    /// Like [`FieldsGenerator::generate`], where `taken` holds the field
+    /// names of the whole struct, so that the additional properties field
+    /// is named apart from them.
+    pub fn generate_taken(&self, taken: &mut HashSet<String>) -> Result<TokenStream, String> {
+
         let mut fields = TokenStream::new();
 
         let required_fields: HashSet<String> = self.obj.required.iter().cloned().collect();
@@ -33,27 +50,25 @@ impl<'a> FieldsGenerator<'a> {
             let (field_type, field_doc) = match field_schema_ref {
                 ReferenceOr::Reference { reference } => {
 
-                    if let Some(type_name) = reference.strip_prefix("#/components/schemas/") {
+                    let type_name = self.ctx.schema_reference(reference)?;
 
-                        let type_ident = format_ident!("{}", type_name.to_pascal_case());
+                    let type_ident = self.ctx.schema_type(type_name)?;
 
-                        let ty = if type_name == self.struct_name {
+                    let ty = if self.ctx.schema_type_name(type_name) == self.struct_name {
 
-                            quote! { Box<#type_ident> }
-                        } else {
-
-                            quote! { #type_ident }
-                        };
-
-                        (ty, quote! {})
+                        quote! { Box<#type_ident> }
                     } else {
 
-                        (quote! { serde_json::Value }, quote! {})
-                    }
+                        quote! { #type_ident }
+                    };
+
+                    (ty, quote! {})
                 }
                 ReferenceOr::Item(schema) => {
 
-                    let rust_type = TypesGenerator::new(schema).generate()?;
+                    let type_name = format!("{}{}", self.struct_name, field_name.to_pascal_case());
+
+                    let rust_type = TypesGenerator::new(self.ctx, &type_name, schema).generate()?;
 
                     let doc_comment =
                         DocsGenerator::generate(schema.schema_data.description.as_deref());
@@ -62,7 +77,12 @@ impl<'a> FieldsGenerator<'a> {
                 }
             };
 
-            let field_type = if required_fields.contains(field_name) {
+            let nullable = matches!(
+                field_schema_ref,
+                ReferenceOr::Item(schema) if schema.schema_data.nullable
+            );
+
+            let field_type = if required_fields.contains(field_name) && !nullable {
 
                 field_type
             } else {
@@ -70,7 +90,9 @@ impl<'a> FieldsGenerator<'a> {
                 quote! { Option<#field_type> }
             };
 
-            let serde_attr = if field_name != &field_name.to_snake_case() {
+            // serde strips `r#` from raw identifiers, any other change needs
+            // a rename
+            let serde_attr = if field_ident.to_string().trim_start_matches("r#") != field_name {
 
                 quote! { #[serde(rename = #field_name)] }
             } else {
@@ -85,10 +107,57 @@ impl<'a> FieldsGenerator<'a> {
             });
         }
 
+        // fixed properties plus `additionalProperties` collect the rest of
+        // the object into a flattened map
+        match &self.obj.additional_properties {
+            Some(additional_properties @ AdditionalProperties::Schema(_))
+            | Some(additional_properties @ AdditionalProperties::Any(true))
+                if !self.obj.properties.is_empty() =>
+            {
+
+                let map_type = map_type(self.ctx, self.struct_name, Some(additional_properties))?;
+
+                let field_ident = format_ident!("{}", claim_field(taken, "additional_properties"));
+
+                fields.extend(quote! {
+                    #[serde(flatten)]
+                    pub #field_ident: #map_type,
+                });
+            }
+            _ => {}
+        }
+
         Ok(fields)
     }
 }
 
+/// Names of the fields generated for the properties of `obj`.
+pub fn field_names(obj: &ObjectType) -> impl Iterator<Item = String> + '_ {
+
+    obj.properties
+        .keys()
+        .map(|name| create_rust_safe_ident(&name.to_snake_case()).to_string())
+}
+
+/// Takes `name`, or the first free suffixed variant of it.
+fn claim_field(taken: &mut HashSet<String>, name: &str) -> String {
+
+    let mut unique = name.to_string();
+
+    let mut suffix = 2;
+
+    while taken.contains(&unique) {
+
+        unique = format!("{name}{suffix}");
+
+        suffix += 1;
+    }
+
+    taken.insert(unique.clone());
+
+    unique
+}
+
 pub fn is_rust_keyword(name: &str) -> bool {
 
     matches!(
@@ -156,6 +225,12 @@ pub fn create_rust_safe_ident(name: &str) -> Ident {
             "Self" => format_ident!("Self_"),
             _ => format_ident!("r#{}", name),
         }
+    } else if name.is_empty() {
+
+        format_ident!("empty")
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+
+        format_ident!("_{}", name)
     } else {
 
         format_ident!("{}", name)
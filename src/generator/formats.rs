@@ -0,0 +1,59 @@
+use openapiv3::{StringFormat, StringType, VariantOrUnknownOrEmpty};
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::generator::context::Context;
+
+/// Crates the generated code needs on top of the template's dependencies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Dependency {
+    Base64,
+    Bytes,
+    Chrono,
+    Url,
+    Uuid,
+}
+
+impl Dependency {
+    /// The `[dependencies]` entry of the generated `Cargo.toml`.
+    pub fn manifest_entry(&self) -> &'static str {
+
+        match self {
+            Dependency::Base64 => r#"base64 = "0.22""#,
+            Dependency::Bytes => r#"bytes = { version = "1", features = ["serde"] }"#,
+            Dependency::Chrono => r#"chrono = { version = "0.4", features = ["serde"] }"#,
+            Dependency::Url => r#"url = { version = "2", features = ["serde"] }"#,
+            Dependency::Uuid => r#"uuid = { version = "1", features = ["serde"] }"#,
+        }
+    }
+}
+
+/// Rust type of a string schema, picked by its `format`. Formats without a
+/// dedicated type stay `String`.
+pub fn string_type(ctx: &Context, schema: &StringType) -> TokenStream {
+
+    let (rust_type, dependency) = match &schema.format {
+        VariantOrUnknownOrEmpty::Item(StringFormat::DateTime) => {
+            (quote! { chrono::DateTime<chrono::Utc> }, Dependency::Chrono)
+        }
+        VariantOrUnknownOrEmpty::Item(StringFormat::Date) => {
+            (quote! { chrono::NaiveDate }, Dependency::Chrono)
+        }
+        VariantOrUnknownOrEmpty::Item(StringFormat::Byte) => {
+            (quote! { Base64 }, Dependency::Base64)
+        }
+        VariantOrUnknownOrEmpty::Item(StringFormat::Binary) => {
+            (quote! { bytes::Bytes }, Dependency::Bytes)
+        }
+        VariantOrUnknownOrEmpty::Unknown(format) => match format.as_str() {
+            "uuid" | "guid" => (quote! { uuid::Uuid }, Dependency::Uuid),
+            "uri" | "url" => (quote! { url::Url }, Dependency::Url),
+            _ => return quote! { String },
+        },
+        _ => return quote! { String },
+    };
+
+    ctx.require(dependency);
+
+    rust_type
+}
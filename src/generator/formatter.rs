@@ -1,5 +1,26 @@
 use std::{path::Path, process::Command};
 
+use proc_macro2::TokenStream;
+
+use crate::{Error, Result};
+
+/// Pretty prints generated code in-process. Tokens that do not parse as a
+/// file are returned unformatted, so the compiler can point at the problem.
+pub fn format_tokens(tokens: &TokenStream) -> String {
+
+    match syn::parse2::<syn::File>(tokens.clone()) {
+        Ok(file) => prettyplease::unparse(&file),
+        Err(e) => {
+
+            tracing::warn!("Could not format generated code: {e}");
+
+            tokens.to_string()
+        }
+    }
+}
+
+/// Runs `cargo fmt` and `cargo clippy --fix` on a written crate. Needs a
+/// toolchain and the crate's dependencies, hence opt-in.
 pub struct Formatter<'a>(&'a str);
 
 impl<'a> Formatter<'a> {
@@ -8,13 +29,11 @@ impl<'a> Formatter<'a> {
         Self(name)
     }
 
-    pub fn format(&self) {
+    pub fn format(&self) -> Result<()> {
 
         let manifest = Path::new(self.0);
 
-        let absolute_path = manifest
-            .canonicalize()
-            .expect("Failed to get absolute path of the manifest");
+        let absolute_path = manifest.canonicalize()?;
 
         tracing::info!("Formatting code in {}...", absolute_path.display());
 
@@ -22,7 +41,7 @@ impl<'a> Formatter<'a> {
             .current_dir(&absolute_path)
             .arg("fmt")
             .output()
-            .expect("Failed to format the code");
+            .map_err(|e| Error::Format(format!("could not run cargo fmt: {e}")))?;
 
         tracing::info!("Code formatted successfully");
 
@@ -35,16 +54,35 @@ impl<'a> Formatter<'a> {
             .arg("--allow-dirty")
             .arg("--allow-no-vcs")
             .output()
-            .expect("Failed to run clippy");
-
-        if output.status.success() {
+            .map_err(|e| Error::Format(format!("could not run cargo clippy: {e}")))?;
 
-            tracing::info!("Clippy fixes completed successfully");
-        } else {
+        if !output.status.success() {
 
             let stderr = String::from_utf8_lossy(&output.stderr);
 
-            panic!("Clippy encountered issues:\n{stderr}");
+            return Err(Error::Format(format!(
+                "clippy encountered issues:\n{stderr}"
+            )));
         }
+
+        tracing::info!("Clippy fixes completed successfully");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    #[test]
+
+    fn test_format_tokens() {
+
+        let tokens = quote::quote! { pub struct Pet { pub id: i64 } };
+
+        assert_eq!(
+            super::format_tokens(&tokens),
+            "pub struct Pet {\n    pub id: i64,\n}\n"
+        );
     }
 }
@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+
+use proc_macro2::TokenStream;
+use serde::Deserialize;
+
+/// User supplied Rust types replacing generated ones, read from a TOML file:
+///
+/// ```toml
+/// [schemas]
+/// BaseItemDto = "my_crate::Item"
+///
+/// [formats]
+/// date-time = "time::OffsetDateTime"
+///
+/// [pointers]
+/// "#/components/schemas/User/properties/Id" = "my_crate::UserId"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TypeMapping {
+    /// Component schema name to Rust path. Mapped schemas are not emitted.
+    #[serde(default)]
+    pub schemas: BTreeMap<String, String>,
+    /// Schema `format` to Rust path.
+    #[serde(default)]
+    pub formats: BTreeMap<String, String>,
+    /// JSON pointer into the spec to Rust path.
+    #[serde(default)]
+    pub pointers: BTreeMap<String, String>,
+}
+
+/// Schema extension carrying the Rust path a schema maps to. Pointer
+/// mappings are applied to the spec through it, specs may also set it
+/// directly.
+pub const RUST_TYPE_EXTENSION: &str = "x-rust-type";
+
+impl TypeMapping {
+    pub fn from_toml(data: &str) -> crate::Result<Self> {
+
+        toml::from_str(data).map_err(|e| crate::Error::TypeMapping(e.to_string()))
+    }
+
+    pub fn schema(&self, name: &str) -> Option<&str> {
+
+        self.schemas.get(name).map(String::as_str)
+    }
+
+    pub fn format(&self, format: &str) -> Option<&str> {
+
+        self.formats.get(format).map(String::as_str)
+    }
+}
+
+/// Parses a configured Rust path such as `my_crate::Item`.
+pub fn rust_path(path: &str) -> Result<TokenStream, String> {
+
+    path.parse()
+        .map_err(|e| format!("Invalid Rust path {path}: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+
+    fn test_from_toml() {
+
+        let mapping = TypeMapping::from_toml(
+            r##"
+            [schemas]
+            Pet = "my::Pet"
+
+            [pointers]
+            "#/components/schemas/User/properties/id" = "my::UserId"
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(mapping.schema("Pet"), Some("my::Pet"));
+
+        assert_eq!(mapping.format("date-time"), None);
+
+        assert_eq!(mapping.pointers.len(), 1);
+
+        assert!(TypeMapping::from_toml("[unknown]").is_err());
+    }
+}
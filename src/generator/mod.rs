@@ -1,20 +1,48 @@
+mod all_of;
+mod builder;
+mod bundle;
+mod context;
+mod diagnostics;
 mod docs;
 mod enums;
 mod fields;
+mod formats;
 mod formatter;
+mod mapping;
+mod module;
+mod naming;
+mod one_of;
 mod openapi;
+mod openapi31;
+mod output;
 mod params;
 mod paths;
+mod responses;
 mod structs;
+mod swagger2;
 mod types;
 mod writer;
 
+pub use all_of::*;
+pub use builder::*;
+pub use bundle::*;
+pub use context::*;
+pub use diagnostics::*;
 pub use docs::*;
 pub use fields::*;
+pub use formats::*;
 pub use formatter::*;
+pub use mapping::*;
+pub use module::*;
+pub use naming::*;
+pub use one_of::*;
 pub use openapi::*;
+pub use openapi31::*;
+pub use output::*;
 pub use params::*;
 pub use paths::*;
+pub use responses::*;
 pub use structs::*;
+pub use swagger2::*;
 pub use types::*;
 pub use writer::*;
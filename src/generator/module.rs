@@ -0,0 +1,222 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{CrateWriter, Error, GeneratedCrate, Generator, MemoryOutput, Result, TypeMapping};
+
+/// Generates the client at build time, as a single module instead of a
+/// crate. In `build.rs`:
+///
+/// ```no_run
+/// falocon::Builder::new("openapi.json").emit_module()?;
+/// # Ok::<(), falocon::Error>(())
+/// ```
+///
+/// Then in the crate, which needs the dependencies listed in
+/// [`GeneratedCrate::dependencies`] next to serde, reqwest and futures:
+///
+/// ```ignore
+/// #[allow(unused_imports)]
+/// mod client {
+///     include!(concat!(env!("OUT_DIR"), "/client.rs"));
+/// }
+/// ```
+pub struct Builder {
+    spec_path: PathBuf,
+    out_dir: Option<PathBuf>,
+    file_name: String,
+    type_mapping: Option<TypeMapping>,
+    type_mapping_file: Option<PathBuf>,
+}
+
+impl Builder {
+    pub fn new(spec_path: impl Into<PathBuf>) -> Self {
+
+        Self {
+            spec_path: spec_path.into(),
+            out_dir: None,
+            file_name: "client.rs".to_string(),
+            type_mapping: None,
+            type_mapping_file: None,
+        }
+    }
+
+    /// Directory to write into, `OUT_DIR` by default.
+    pub fn out_dir(mut self, out_dir: impl Into<PathBuf>) -> Self {
+
+        self.out_dir = Some(out_dir.into());
+
+        self
+    }
+
+    /// Name of the emitted file, `client.rs` by default.
+    pub fn file_name(mut self, file_name: impl Into<String>) -> Self {
+
+        self.file_name = file_name.into();
+
+        self
+    }
+
+    pub fn type_mapping(mut self, type_mapping: TypeMapping) -> Self {
+
+        self.type_mapping = Some(type_mapping);
+
+        self
+    }
+
+    /// TOML file to read the type mapping from, see
+    /// [`TypeMapping::from_toml`]. Changes to it rerun the build script.
+    pub fn type_mapping_file(mut self, path: impl Into<PathBuf>) -> Self {
+
+        self.type_mapping_file = Some(path.into());
+
+        self
+    }
+
+    /// Writes the module and returns its path. Skipped operations and schemas
+    /// are reported as cargo warnings, and the build script reruns when the
+    /// spec, a file it references or the type mapping file changes.
+    pub fn emit_module(self) -> Result<PathBuf> {
+
+        let out_dir = match self.out_dir {
+            Some(out_dir) => out_dir,
+            None => std::env::var_os("OUT_DIR")
+                .map(PathBuf::from)
+                .ok_or(Error::OutDir)?,
+        };
+
+        let mut builder = Generator::builder().spec(&self.spec_path);
+
+        if let Some(type_mapping) = self.type_mapping {
+
+            builder = builder.type_mapping(type_mapping);
+        }
+
+        if let Some(path) = &self.type_mapping_file {
+
+            println!("cargo:rerun-if-changed={}", path.display());
+
+            let data = std::fs::read_to_string(path).map_err(|source| Error::Read {
+                path: path.display().to_string(),
+                source,
+            })?;
+
+            builder = builder.type_mapping(TypeMapping::from_toml(&data)?);
+        }
+
+        let generated = builder.generate()?;
+
+        for source in &generated.sources {
+
+            println!("cargo:rerun-if-changed={}", source.display());
+        }
+
+        for diagnostic in &generated.diagnostics {
+
+            println!("cargo:warning={diagnostic}");
+        }
+
+        let path = out_dir.join(&self.file_name);
+
+        std::fs::write(&path, generated.module()?)?;
+
+        Ok(path)
+    }
+}
+
+impl GeneratedCrate {
+    /// The crate as the contents of a single module, for `include!`. Every
+    /// `mod` declaration is replaced by the file it refers to.
+    pub fn module(&self) -> Result<String> {
+
+        let mut output = MemoryOutput::new();
+
+        CrateWriter::new("", self.types.clone(), self.methods.clone())
+            .with_dependencies(self.dependencies.clone())
+            .write_to(&mut output)?;
+
+        let files = output.files();
+
+        let lib = files
+            .get(Path::new("src/lib.rs"))
+            .ok_or_else(|| missing_module(Path::new("src/lib.rs")))?;
+
+        inline_modules(&files, Path::new("src"), lib)
+    }
+}
+
+/// `source` with its `mod name;` declarations replaced by inline modules,
+/// looked up in `dir` like rustc does.
+fn inline_modules(files: &BTreeMap<PathBuf, String>, dir: &Path, source: &str) -> Result<String> {
+
+    let mut module = String::new();
+
+    for line in source.lines() {
+
+        let declaration = line.trim();
+
+        let Some(name) = declaration
+            .strip_prefix("pub ")
+            .unwrap_or(declaration)
+            .strip_prefix("mod ")
+            .and_then(|rest| rest.strip_suffix(';'))
+        else {
+
+            module.push_str(line);
+
+            module.push('\n');
+
+            continue;
+        };
+
+        // raw identifiers, such as `r#type`, name the file without `r#`
+        let name = name.strip_prefix("r#").unwrap_or(name);
+
+        let file_path = dir.join(format!("{name}.rs"));
+
+        let mod_path = dir.join(name).join("mod.rs");
+
+        let contents = files
+            .get(&file_path)
+            .or_else(|| files.get(&mod_path))
+            .ok_or_else(|| missing_module(&file_path))?;
+
+        module.push_str(declaration.trim_end_matches(';'));
+
+        module.push_str(" {\n");
+
+        module.push_str(&inline_modules(files, &dir.join(name), contents)?);
+
+        module.push_str("}\n");
+    }
+
+    Ok(module)
+}
+
+fn missing_module(path: &Path) -> Error {
+
+    Error::MissingModule(path.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+
+    #[test]
+
+    fn test_missing_module() {
+
+        let files = [(
+            std::path::PathBuf::from("src/lib.rs"),
+            "mod types;\n".to_string(),
+        )]
+        .into();
+
+        let result = super::inline_modules(&files, std::path::Path::new("src"), "mod types;\n");
+
+        assert!(matches!(
+            result,
+            Err(crate::Error::MissingModule(path)) if path == "src/types.rs"
+        ));
+    }
+}
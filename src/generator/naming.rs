@@ -0,0 +1,345 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use heck::{ToPascalCase, ToSnakeCase};
+use openapiv3::{OpenAPI, Operation, PathItem, ReferenceOr};
+
+use crate::generator::diagnostics::json_pointer;
+
+/// Operation extension overriding the name of the generated request.
+pub const RUST_NAME_EXTENSION: &str = "x-rust-name";
+
+/// Names the generated code uses unqualified, which types cannot take.
+const RESERVED_TYPE_NAMES: [&str; 15] = [
+    "Base64",
+    "Box",
+    "ClientPrelude",
+    "Cow",
+    "Deserialize",
+    "FromResponse",
+    "HashMap",
+    "Method",
+    "Option",
+    "Request",
+    "Route",
+    "Self",
+    "Serialize",
+    "StatusCode",
+    "String",
+];
+
+/// Types generated for every operation, next to the request struct itself.
+/// An inline request body claims its `{Op}Body` name like any nested type.
+const OPERATION_TYPE_SUFFIXES: [&str; 6] =
+    ["", "Params", "Headers", "Cookies", "Response", "Error"];
+
+/// A generated name that was changed because it clashed with another one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rename {
+    /// JSON pointer of the renamed schema or operation, `None` for nested
+    /// types.
+    pub pointer: Option<String>,
+    pub from: String,
+    pub to: String,
+}
+
+impl fmt::Display for Rename {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+
+        if let Some(pointer) = &self.pointer {
+
+            write!(f, "#{pointer}: ")?;
+        }
+
+        write!(f, "{} renamed to {}", self.from, self.to)
+    }
+}
+
+/// Names of the generated types and operations, unique across the spec.
+#[derive(Debug, Default)]
+pub struct Names {
+    /// PascalCase type name by component schema name.
+    pub schemas: HashMap<String, String>,
+    /// snake_case name by operation JSON pointer.
+    pub operations: HashMap<String, String>,
+    /// Every type name taken, by schemas, operations or reserved ones.
+    pub types: HashSet<String>,
+    pub renames: Vec<Rename>,
+}
+
+impl Names {
+    /// Names component schemas first, in spec order, then operations.
+    ///
+    /// An operation is named after its `x-rust-name` extension, its
+    /// `operationId`, or else its method and path template, such as
+    /// `get_users_by_user_id_items` for `GET /users/{userId}/items`. Explicit
+    /// names are claimed before synthesized ones.
+    ///
+    /// A name already taken gets the first free `2`, `3`... suffix. For an
+    /// operation, the suffix must free all the types it generates.
+    pub fn new(openapi: &OpenAPI) -> Self {
+
+        let mut names = Self {
+            types: RESERVED_TYPE_NAMES
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            ..Self::default()
+        };
+
+        if let Some(components) = &openapi.components {
+
+            for name in components.schemas.keys() {
+
+                let pointer = json_pointer(&["components", "schemas", name]);
+
+                let type_name = names.claim_type(Some(pointer), &schema_type_name(name));
+
+                names.schemas.insert(name.clone(), type_name);
+            }
+        }
+
+        let mut explicit = Vec::new();
+
+        let mut synthesized = Vec::new();
+
+        for (path, item) in &openapi.paths.paths {
+
+            let ReferenceOr::Item(item) = item else {
+
+                continue;
+            };
+
+            for (method, op) in operations(item) {
+
+                let pointer = json_pointer(&["paths", path, method]);
+
+                match explicit_name(op) {
+                    Some(name) => explicit.push((pointer, operation_name(name.to_snake_case()))),
+                    None => synthesized.push((pointer, synthesize_name(method, path))),
+                }
+            }
+        }
+
+        let mut taken = HashSet::new();
+
+        for (pointer, name) in explicit.into_iter().chain(synthesized) {
+
+            let mut unique = name.clone();
+
+            let mut suffix = 2;
+
+            while taken.contains(&unique) || !names.operation_types_free(&unique) {
+
+                unique = format!("{name}{suffix}");
+
+                suffix += 1;
+            }
+
+            let struct_name = unique.to_pascal_case();
+
+            for type_suffix in OPERATION_TYPE_SUFFIXES {
+
+                names.types.insert(format!("{struct_name}{type_suffix}"));
+            }
+
+            if unique != name {
+
+                names.renames.push(Rename {
+                    pointer: Some(pointer.clone()),
+                    from: name,
+                    to: unique.clone(),
+                });
+            }
+
+            taken.insert(unique.clone());
+
+            names.operations.insert(pointer, unique);
+        }
+
+        names
+    }
+
+    /// Takes `name`, or the first free suffixed variant of it, recording the
+    /// rename.
+    pub fn claim_type(&mut self, pointer: Option<String>, name: &str) -> String {
+
+        let mut unique = name.to_string();
+
+        let mut suffix = 2;
+
+        while self.types.contains(&unique) {
+
+            unique = format!("{name}{suffix}");
+
+            suffix += 1;
+        }
+
+        self.types.insert(unique.clone());
+
+        if unique != name {
+
+            self.renames.push(Rename {
+                pointer,
+                from: name.to_string(),
+                to: unique.clone(),
+            });
+        }
+
+        unique
+    }
+
+    fn operation_types_free(&self, name: &str) -> bool {
+
+        let struct_name = name.to_pascal_case();
+
+        OPERATION_TYPE_SUFFIXES
+            .iter()
+            .all(|suffix| !self.types.contains(&format!("{struct_name}{suffix}")))
+    }
+}
+
+/// Operations of a path item with their lowercase method, in the order
+/// `PathsGenerator` generates them.
+pub fn operations(item: &PathItem) -> impl Iterator<Item = (&'static str, &Operation)> {
+
+    [
+        ("get", &item.get),
+        ("post", &item.post),
+        ("put", &item.put),
+        ("delete", &item.delete),
+        ("patch", &item.patch),
+        ("head", &item.head),
+        ("options", &item.options),
+    ]
+    .into_iter()
+    .filter_map(|(method, op)| op.as_ref().map(|op| (method, op)))
+}
+
+fn explicit_name(op: &Operation) -> Option<&str> {
+
+    op.extensions
+        .get(RUST_NAME_EXTENSION)
+        .and_then(|name| name.as_str())
+        .or(op.operation_id.as_deref())
+        .filter(|name| !name.to_snake_case().is_empty())
+}
+
+/// PascalCase name of a component schema, prefixed when it could not start
+/// an identifier, such as `2fa`.
+fn schema_type_name(name: &str) -> String {
+
+    let type_name = name.to_pascal_case();
+
+    if type_name.starts_with(|c: char| c.is_alphabetic()) {
+
+        type_name
+    } else {
+
+        format!("Schema{type_name}")
+    }
+}
+
+/// `name` prefixed when it could not name a module, such as `2fa` or
+/// `crate`. Other keywords are written as raw identifiers.
+fn operation_name(name: String) -> String {
+
+    if name.starts_with(|c: char| c.is_ascii_digit())
+        || matches!(name.as_str(), "crate" | "self" | "super")
+    {
+
+        format!("op_{name}")
+    } else {
+
+        name
+    }
+}
+
+fn synthesize_name(method: &str, path: &str) -> String {
+
+    let mut name = method.to_string();
+
+    let mut segments = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .peekable();
+
+    if segments.peek().is_none() {
+
+        name.push_str("_root");
+    }
+
+    for segment in segments {
+
+        let segment = match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            Some(param) => format!("by_{}", param.to_snake_case()),
+            None => segment.to_snake_case(),
+        };
+
+        if !segment.is_empty() {
+
+            name.push('_');
+
+            name.push_str(&segment);
+        }
+    }
+
+    name
+}
+
+#[cfg(test)]
+mod tests {
+
+    #[test]
+
+    fn test_names() {
+
+        let openapi: openapiv3::OpenAPI = serde_json::from_str(
+            r##"{
+                "openapi": "3.0.3",
+                "info": { "title": "t", "version": "1" },
+                "paths": {
+                    "/users/{userId}/items": {
+                        "get": { "responses": {} },
+                        "post": { "operationId": "getUsersByUserIdItems", "responses": {} }
+                    },
+                    "/": { "get": { "x-rust-name": "index", "operationId": "root", "responses": {} } },
+                    "/pets": { "get": { "operationId": "pet", "responses": {} } }
+                },
+                "components": {
+                    "schemas": {
+                        "foo_bar": { "type": "string" },
+                        "FooBar": { "type": "string" },
+                        "PetParams": { "type": "string" },
+                        "Request": { "type": "string" }
+                    }
+                }
+            }"##,
+        )
+        .unwrap();
+
+        let names = super::Names::new(&openapi);
+
+        assert_eq!(names.schemas["foo_bar"], "FooBar");
+
+        assert_eq!(names.schemas["FooBar"], "FooBar2");
+
+        assert_eq!(names.schemas["Request"], "Request2");
+
+        assert_eq!(
+            names.operations["/paths/~1users~1{userId}~1items/post"],
+            "get_users_by_user_id_items"
+        );
+
+        assert_eq!(
+            names.operations["/paths/~1users~1{userId}~1items/get"],
+            "get_users_by_user_id_items2"
+        );
+
+        assert_eq!(names.operations["/paths/~1/get"], "index");
+
+        assert_eq!(names.operations["/paths/~1pets/get"], "pet2");
+
+        assert_eq!(names.renames.len(), 4);
+    }
+}
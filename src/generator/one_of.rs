@@ -0,0 +1,406 @@
+use std::collections::HashSet;
+
+use heck::ToPascalCase;
+use openapiv3::{Discriminator, ReferenceOr, Schema, SchemaKind, Type};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+use crate::generator::{
+    all_of::object_type, context::Context, create_rust_safe_ident, docs::DocsGenerator,
+    types::TypesGenerator,
+};
+
+pub struct OneOfGenerator<'a> {
+    ctx: &'a Context,
+    enum_name: &'a str,
+    parts: &'a [ReferenceOr<Schema>],
+    discriminator: Option<&'a Discriminator>,
+}
+
+impl<'a> OneOfGenerator<'a> {
+    pub fn new(
+        ctx: &'a Context,
+        enum_name: &'a str,
+        parts: &'a [ReferenceOr<Schema>],
+        discriminator: Option<&'a Discriminator>,
+    ) -> Self {
+
+        Self {
+            ctx,
+            enum_name,
+            parts,
+            discriminator,
+        }
+    }
+
+    /// The enum definition, internally tagged when the schema has a
+    /// discriminator and untagged otherwise.
+    ///
+    /// When the variants declare the discriminator property themselves, as
+    /// they do when they share a base schema through `allOf`, serde's
+    /// internal tagging would strip the tag before decoding the variant and
+    /// write it twice. Such enums are serialized untagged instead, and
+    /// deserialized by peeking at the tag and passing the whole object on.
+    /// Inline variants are selected by the `enum` values of their tag
+    /// property, or tried in order when they list none.
+    pub fn generate(&self, doc_comment: TokenStream) -> Result<TokenStream, String> {
+
+        let enum_ident = format_ident!("{}", self.enum_name);
+
+        let tag_in_variants = match self.discriminator {
+            Some(discriminator) => self.tag_in_variants(&discriminator.property_name)?,
+            None => false,
+        };
+
+        let mut variants = TokenStream::new();
+
+        let mut tagged = Vec::new();
+
+        // inline variants without known tag values
+        let mut untagged = Vec::new();
+
+        let mut used = HashSet::new();
+
+        for (index, part) in self.parts.iter().enumerate() {
+
+            let (variant_name, rust_type, doc_comment, reference) = match part {
+                ReferenceOr::Reference { reference } => {
+
+                    let type_name = self.ctx.schema_reference(reference)?;
+
+                    let type_ident = self.ctx.schema_type(type_name)?;
+
+                    let variant_name = self.ctx.schema_type_name(type_name);
+
+                    let rust_type = if variant_name == self.enum_name {
+
+                        quote! { Box<#type_ident> }
+                    } else {
+
+                        quote! { #type_ident }
+                    };
+
+                    (variant_name, rust_type, quote! {}, Some(reference.as_str()))
+                }
+                ReferenceOr::Item(schema) => {
+
+                    let variant_name = variant_name(schema, index);
+
+                    let type_name = format!("{}{}", self.enum_name, variant_name);
+
+                    (
+                        variant_name,
+                        TypesGenerator::new(self.ctx, &type_name, schema).generate()?,
+                        DocsGenerator::generate(schema.schema_data.description.as_deref()),
+                        None,
+                    )
+                }
+            };
+
+            let variant_name = if used.contains(&variant_name) {
+
+                format!("{variant_name}{index}")
+            } else {
+
+                variant_name
+            };
+
+            used.insert(variant_name.clone());
+
+            let variant_ident = create_rust_safe_ident(&variant_name);
+
+            let rename = match (self.discriminator, reference) {
+                (Some(discriminator), Some(reference)) => {
+
+                    let value = discriminator_value(discriminator, reference);
+
+                    let rename = if tag_in_variants {
+
+                        quote! {}
+                    } else {
+
+                        quote! { #[serde(rename = #value)] }
+                    };
+
+                    tagged.push((value, variant_ident.clone()));
+
+                    rename
+                }
+                (Some(discriminator), None) if tag_in_variants => {
+
+                    let values = property_values(self.ctx, part, &discriminator.property_name);
+
+                    if values.is_empty() {
+
+                        untagged.push(variant_ident.clone());
+                    }
+
+                    for value in values {
+
+                        tagged.push((value, variant_ident.clone()));
+                    }
+
+                    quote! {}
+                }
+                _ => quote! {},
+            };
+
+            variants.extend(quote! {
+                #doc_comment
+                #rename
+                #variant_ident(#rust_type),
+            });
+        }
+
+        let Some(discriminator) = self.discriminator.filter(|_| tag_in_variants) else {
+
+            let serde_attr = match self.discriminator {
+                Some(discriminator) => {
+
+                    let tag = &discriminator.property_name;
+
+                    quote! { #[serde(tag = #tag)] }
+                }
+                None => quote! { #[serde(untagged)] },
+            };
+
+            return Ok(quote! {
+                #doc_comment
+                #[derive(Debug, Clone, Serialize, Deserialize)]
+                #serde_attr
+                pub enum #enum_ident {
+                    #variants
+                }
+            });
+        };
+
+        let tag = &discriminator.property_name;
+
+        let tags: Vec<&String> = tagged.iter().map(|(value, _)| value).collect();
+
+        let arms = tagged.iter().map(|(value, variant_ident)| {
+
+            quote! {
+                Some(#value) => serde_json::from_value(value)
+                    .map(Self::#variant_ident)
+                    .map_err(serde::de::Error::custom),
+            }
+        });
+
+        let unknown = quote! {
+            Err(serde::de::Error::unknown_variant(other, &[#(#tags),*]))
+        };
+
+        let unknown = if untagged.is_empty() {
+
+            unknown
+        } else {
+
+            quote! {
+                {
+                    #(
+                        if let Ok(variant) = serde_json::from_value(value.clone()) {
+                            return Ok(Self::#untagged(variant));
+                        }
+                    )*
+
+                    #unknown
+                }
+            }
+        };
+
+        Ok(quote! {
+            #doc_comment
+            #[derive(Debug, Clone, Serialize)]
+            #[serde(untagged)]
+            pub enum #enum_ident {
+                #variants
+            }
+
+            impl<'de> Deserialize<'de> for #enum_ident {
+                fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+                where
+                    D: serde::Deserializer<'de>,
+                {
+                    let value = serde_json::Value::deserialize(deserializer)?;
+
+                    match value.get(#tag).and_then(serde_json::Value::as_str) {
+                        #(#arms)*
+                        Some(other) => #unknown,
+                        None => Err(serde::de::Error::missing_field(#tag)),
+                    }
+                }
+            }
+        })
+    }
+
+    /// Whether the variants declare `property`, the discriminator. Variants
+    /// must agree, as the enum is tagged either by serde or by the variants.
+    fn tag_in_variants(&self, property: &str) -> Result<bool, String> {
+
+        let declaring = self
+            .parts
+            .iter()
+            .filter(|part| property_schema(self.ctx, part, property, 0).is_some())
+            .count();
+
+        match declaring {
+            0 => Ok(false),
+            declaring if declaring == self.parts.len() => Ok(true),
+            _ => Err(format!(
+                "Discriminator property {property} is declared by only some oneOf variants"
+            )),
+        }
+    }
+}
+
+/// The schema of `property` in `schema`, declared directly or through its
+/// `allOf` parts.
+fn property_schema(
+    ctx: &Context,
+    schema: &ReferenceOr<Schema>,
+    property: &str,
+    depth: usize,
+) -> Option<ReferenceOr<Schema>> {
+
+    // bounds `allOf` cycles
+    if depth > 32 {
+
+        return None;
+    }
+
+    let schema = ctx.schema(schema).ok()?;
+
+    if let Some(property) =
+        object_type(schema).and_then(|obj| obj.properties.get(property).cloned())
+    {
+
+        return Some(property.unbox());
+    }
+
+    match &schema.schema_kind {
+        SchemaKind::AllOf { all_of } => all_of
+            .iter()
+            .find_map(|part| property_schema(ctx, part, property, depth + 1)),
+        _ => None,
+    }
+}
+
+/// The `enum` values of the string `property` of `schema`.
+fn property_values(ctx: &Context, schema: &ReferenceOr<Schema>, property: &str) -> Vec<String> {
+
+    let Some(property) = property_schema(ctx, schema, property, 0) else {
+
+        return Vec::new();
+    };
+
+    match ctx.schema(&property).map(|schema| &schema.schema_kind) {
+        Ok(SchemaKind::Type(Type::String(string))) => {
+            string.enumeration.iter().flatten().cloned().collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// The payload value selecting `reference`. Falls back to the schema name,
+/// which is what the spec implies when `mapping` does not list it.
+fn discriminator_value(discriminator: &Discriminator, reference: &str) -> String {
+
+    let schema_name = reference.rsplit('/').next().unwrap_or(reference);
+
+    discriminator
+        .mapping
+        .iter()
+        .find(|(_, target)| target.as_str() == reference || target.as_str() == schema_name)
+        .map(|(value, _)| value.clone())
+        .unwrap_or_else(|| schema_name.to_string())
+}
+
+fn variant_name(schema: &Schema, index: usize) -> String {
+
+    if let Some(title) = &schema.schema_data.title {
+
+        return title.to_pascal_case();
+    }
+
+    match &schema.schema_kind {
+        SchemaKind::Type(Type::String(_)) => "String".to_string(),
+        SchemaKind::Type(Type::Integer(_)) => "Integer".to_string(),
+        SchemaKind::Type(Type::Number(_)) => "Number".to_string(),
+        SchemaKind::Type(Type::Boolean(_)) => "Boolean".to_string(),
+        SchemaKind::Type(Type::Array(_)) => "Array".to_string(),
+        SchemaKind::Type(Type::Object(_)) => "Object".to_string(),
+        _ => format!("Variant{index}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    #[test]
+
+    fn test_discriminator_value() {
+
+        let discriminator: openapiv3::Discriminator = serde_json::from_str(
+            r##"{ "propertyName": "petType", "mapping": { "dog": "#/components/schemas/Dog" } }"##,
+        )
+        .unwrap();
+
+        assert_eq!(
+            super::discriminator_value(&discriminator, "#/components/schemas/Dog"),
+            "dog"
+        );
+
+        assert_eq!(
+            super::discriminator_value(&discriminator, "#/components/schemas/Cat"),
+            "Cat"
+        );
+    }
+
+    #[test]
+
+    fn test_tag_in_some_variants() {
+
+        let openapi: openapiv3::OpenAPI = serde_json::from_str(
+            r##"{
+                "openapi": "3.0.3",
+                "info": { "title": "t", "version": "1" },
+                "paths": {},
+                "components": {
+                    "schemas": {
+                        "Dog": { "type": "object", "properties": { "petType": { "type": "string" } } },
+                        "Cat": { "type": "object", "properties": { "lives": { "type": "integer" } } },
+                        "Pet": {
+                            "oneOf": [{ "$ref": "#/components/schemas/Dog" }, { "$ref": "#/components/schemas/Cat" }],
+                            "discriminator": { "propertyName": "petType" }
+                        }
+                    }
+                }
+            }"##,
+        )
+        .unwrap();
+
+        let ctx = crate::Context::new().with_spec(&openapi);
+
+        let openapiv3::ReferenceOr::Item(pet) =
+            &openapi.components.as_ref().unwrap().schemas["Pet"]
+        else {
+
+            unreachable!();
+        };
+
+        let openapiv3::SchemaKind::OneOf { one_of } = &pet.schema_kind else {
+
+            unreachable!();
+        };
+
+        let generated =
+            super::OneOfGenerator::new(&ctx, "Pet", one_of, pet.schema_data.discriminator.as_ref())
+                .generate(quote::quote! {});
+
+        assert_eq!(
+            generated.unwrap_err(),
+            "Discriminator property petType is declared by only some oneOf variants"
+        );
+    }
+}
@@ -1,21 +1,113 @@
-use std::collections::HashMap;
+use std::path::PathBuf;
 
 use openapiv3::{OpenAPI, ReferenceOr};
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{format_ident, quote};
 
-use crate::{PathsGenerator, generator::StructsGenerator};
+use crate::{
+    Context, Dependency, Diagnostic, Error, OperationMap, PathsGenerator, RUST_TYPE_EXTENSION,
+    Rename, Result, TypeMapping, bundle_external_refs, convert_swagger_2,
+    generator::StructsGenerator, is_openapi_3_1, is_swagger_2, json_pointer, normalize_openapi_3_1,
+    parse_document, schema_ref_type,
+};
 
 pub struct OpenAPIGenerator {
     openapi: OpenAPI,
+    ctx: Context,
+    sources: Vec<PathBuf>,
 }
 
 impl OpenAPIGenerator {
-    pub fn from_json(data: &str) -> Self {
+    pub fn from_json(data: &str) -> Result<Self> {
 
-        Self {
-            openapi: serde_json::from_str(data).expect("Could not deserialize input"),
+        Self::from_value(serde_json::from_str(data).map_err(|e| Error::Spec(e.to_string()))?)
+    }
+
+    pub fn from_yaml(data: &str) -> Result<Self> {
+
+        Self::from_value(serde_yaml::from_str(data).map_err(|e| Error::Spec(e.to_string()))?)
+    }
+
+    /// Reads the spec stored at `path`, JSON or YAML, merging the files its
+    /// references point to.
+    pub fn from_spec(path: &str, data: &str) -> Result<Self> {
+
+        let path = std::path::Path::new(path);
+
+        let mut spec = parse_document(path, data).map_err(Error::Spec)?;
+
+        let sources = bundle_external_refs(&mut spec, path).map_err(Error::Reference)?;
+
+        Ok(Self {
+            sources,
+            ..Self::from_value(spec)?
+        })
+    }
+
+    /// Swagger 2.0 documents are converted and OpenAPI 3.1 documents are
+    /// normalized into the 3.0 model first.
+    fn from_value(mut spec: serde_json::Value) -> Result<Self> {
+
+        if is_swagger_2(&spec) {
+
+            spec = convert_swagger_2(spec);
+        } else if is_openapi_3_1(&spec) {
+
+            normalize_openapi_3_1(&mut spec);
+        }
+
+        let openapi: OpenAPI =
+            serde_json::from_value(spec).map_err(|e| Error::Spec(e.to_string()))?;
+
+        let ctx = Context::new().with_spec(&openapi);
+
+        Ok(Self {
+            openapi,
+            ctx,
+            sources: Vec::new(),
+        })
+    }
+
+    /// Replaces generated types with the user supplied ones in `type_mapping`.
+    pub fn with_type_mapping(mut self, mut type_mapping: TypeMapping) -> Result<Self> {
+
+        let mut spec =
+            serde_json::to_value(&self.openapi).map_err(|e| Error::TypeMapping(e.to_string()))?;
+
+        for (pointer, path) in std::mem::take(&mut type_mapping.pointers) {
+
+            let pointer = pointer.trim_start_matches('#');
+
+            // a whole component schema maps like a schema name, so that its
+            // references follow as well
+            if let Some(name) = pointer.strip_prefix("/components/schemas/")
+                && !name.contains('/')
+            {
+
+                type_mapping.schemas.insert(name.to_string(), path);
+
+                continue;
+            }
+
+            let Some(serde_json::Value::Object(schema)) = spec.pointer_mut(pointer) else {
+
+                return Err(Error::TypeMapping(format!(
+                    "pointer #{pointer} matches no schema"
+                )));
+            };
+
+            // replaces `$ref`s as well, which cannot carry extensions
+            schema.retain(|key, _| key == "description" || key == "nullable");
+
+            schema.insert(RUST_TYPE_EXTENSION.to_string(), path.into());
         }
+
+        self.openapi =
+            serde_json::from_value(spec).map_err(|e| Error::TypeMapping(e.to_string()))?;
+
+        self.ctx = Context::with_type_mapping(type_mapping).with_spec(&self.openapi);
+
+        Ok(self)
     }
 
     pub fn gen_types(&self) -> TokenStream {
@@ -34,32 +126,88 @@ impl OpenAPIGenerator {
 
         for (name, schema) in &components.schemas {
 
-            let ReferenceOr::Item(schema) = schema else {
+            // mapped to a user supplied type, nothing to emit
+            if self.ctx.type_mapping().schema(name).is_some() {
 
                 continue;
+            }
+
+            let type_name = self.ctx.schema_type_name(name);
+
+            let generated = match schema {
+                ReferenceOr::Item(schema) => {
+                    StructsGenerator::new(&self.ctx, &type_name, schema).generate()
+                }
+                // a component that only refers to another one is an alias
+                ReferenceOr::Reference { .. } => {
+                    schema_ref_type(&self.ctx, name, schema).map(|rust_type| {
+
+                        let type_ident = format_ident!("{}", type_name);
+
+                        quote! { pub type #type_ident = #rust_type; }
+                    })
+                }
             };
 
-            output.extend(StructsGenerator::new(name, schema).generate());
+            let inline_types = self.ctx.take_inline_types();
+
+            match generated {
+                Ok(generated) => {
+
+                    output.extend(generated);
+
+                    output.extend(inline_types);
+                }
+                Err(reason) => self
+                    .ctx
+                    .report(json_pointer(&["components", "schemas", name]), reason),
+            }
         }
 
         output
     }
 
-    pub fn gen_methods(&self) -> HashMap<String, TokenStream> {
+    /// Operations and schemas skipped by the generation so far.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+
+        self.ctx.diagnostics()
+    }
+
+    /// Files the references of the spec were read from.
+    pub fn sources(&self) -> &[PathBuf] {
+
+        &self.sources
+    }
+
+    /// Names changed by the generation so far to avoid clashes.
+    pub fn renames(&self) -> Vec<Rename> {
+
+        self.ctx.renames()
+    }
 
-        let mut output = HashMap::new();
+    /// Extra crates the code generated so far depends on.
+    pub fn dependencies(&self) -> Vec<Dependency> {
+
+        self.ctx.dependencies()
+    }
+
+    pub fn gen_methods(&self) -> OperationMap {
+
+        let mut output = OperationMap::new();
 
         for path in self.openapi.paths.paths.keys() {
 
             let ReferenceOr::Item(path_item) = &self.openapi.paths.paths[path] else {
 
+                self.ctx.report(
+                    json_pointer(&["paths", path]),
+                    "Path item references are not supported",
+                );
+
                 continue;
             };
 
-            if let Ok(map) = PathsGenerator::new(path, path_item).generate() {
-
-                output.extend(map);
-            }
+            output.extend(PathsGenerator::new(&self.ctx, path, path_item).generate());
         }
 
         output
@@ -67,7 +215,6 @@ impl OpenAPIGenerator {
 }
 
 #[cfg(test)]
-
 mod tests {
 
     #[test]
@@ -76,10 +223,89 @@ mod tests {
 
         let data = include_str!("../../tests/openapi.json");
 
-        let generator = super::OpenAPIGenerator::from_json(data);
+        let generator = super::OpenAPIGenerator::from_json(data).unwrap();
 
         let structs = generator.gen_types();
 
         dbg!(structs.to_string());
     }
+
+    #[test]
+
+    fn test_from_spec_yaml() {
+
+        let data = include_str!("../../tests/openapi.json");
+
+        let value: serde_json::Value = serde_json::from_str(data).unwrap();
+
+        let yaml = serde_yaml::to_string(&value).unwrap();
+
+        let json = super::OpenAPIGenerator::from_spec("openapi.json", data).unwrap();
+
+        let by_extension = super::OpenAPIGenerator::from_spec("openapi.yaml", &yaml).unwrap();
+
+        let sniffed = super::OpenAPIGenerator::from_spec("openapi", &yaml).unwrap();
+
+        assert_eq!(
+            json.gen_types().to_string(),
+            by_extension.gen_types().to_string()
+        );
+
+        assert_eq!(
+            json.gen_types().to_string(),
+            sniffed.gen_types().to_string()
+        );
+    }
+
+    #[test]
+
+    fn test_diagnostics() {
+
+        let generator = super::OpenAPIGenerator::from_yaml(
+            r##"
+            openapi: 3.0.3
+            info: { title: t, version: "1" }
+            paths:
+              /items/{id}:
+                get:
+                  operationId: getItem
+                  parameters:
+                    - $ref: "#/components/parameters/Missing"
+                  responses: { "204": { description: ok } }
+            components:
+              schemas:
+                Item:
+                  $ref: "#/components/schemas/Other"
+                Tags:
+                  type: array
+                  items:
+                    $ref: "#/paths/~1items~1{id}/get"
+            "##,
+        )
+        .unwrap();
+
+        generator.gen_methods();
+
+        generator.gen_types();
+
+        assert_eq!(
+            generator.diagnostics(),
+            vec![
+                super::Diagnostic {
+                    pointer: "/paths/~1items~1{id}/get".to_string(),
+                    reason: "Unresolved reference #/components/parameters/Missing".to_string(),
+                },
+                super::Diagnostic {
+                    pointer: "/components/schemas/Item".to_string(),
+                    reason: "Unresolved reference #/components/schemas/Other".to_string(),
+                },
+                super::Diagnostic {
+                    pointer: "/components/schemas/Tags".to_string(),
+                    reason: "Unsupported reference #/paths/~1items~1{id}/get".to_string(),
+                },
+            ]
+        );
+
+        assert!(!generator.gen_types().to_string().contains("Other"));
+    }
 }
@@ -0,0 +1,248 @@
+use serde_json::{Map, Value};
+
+/// Keys holding maps keyed by user chosen names, whose values are walked but
+/// which are no schema themselves.
+pub(crate) const NAME_MAPS: [&str; 13] = [
+    "properties",
+    "patternProperties",
+    "$defs",
+    "definitions",
+    "schemas",
+    "responses",
+    "parameters",
+    "requestBodies",
+    "headers",
+    "securitySchemes",
+    "callbacks",
+    "content",
+    "paths",
+];
+
+/// Keys holding instance data rather than schemas.
+pub(crate) const DATA_KEYS: [&str; 5] = ["default", "enum", "const", "example", "examples"];
+
+/// Whether `spec` is an OpenAPI 3.1 document.
+pub fn is_openapi_3_1(spec: &Value) -> bool {
+
+    spec.get("openapi")
+        .and_then(Value::as_str)
+        .is_some_and(|version| version.starts_with("3.1"))
+}
+
+/// Rewrites an OpenAPI 3.1 document into the 3.0 shape the generators read:
+///
+/// - `type: [T, "null"]` and `null` members of `oneOf`/`anyOf` become
+///   `nullable: true`, several non-null types become an `anyOf`
+/// - `const` becomes a single value `enum`
+/// - numeric `exclusiveMinimum`/`exclusiveMaximum` become the boolean form
+/// - `examples` arrays become `example`
+/// - `contentEncoding: base64` becomes `format: byte`
+/// - `webhooks` and `jsonSchemaDialect` are dropped, a missing `paths` is
+///   added
+pub fn normalize_openapi_3_1(spec: &mut Value) {
+
+    let Value::Object(root) = spec else {
+
+        return;
+    };
+
+    root.insert("openapi".to_string(), "3.0.3".into());
+
+    root.remove("webhooks");
+
+    root.remove("jsonSchemaDialect");
+
+    root.entry("paths")
+        .or_insert_with(|| Value::Object(Map::new()));
+
+    for (key, value) in root.iter_mut() {
+
+        walk(value, NAME_MAPS.contains(&key.as_str()));
+    }
+}
+
+fn walk(value: &mut Value, name_map: bool) {
+
+    match value {
+        Value::Array(items) => {
+            for item in items {
+
+                walk(item, false);
+            }
+        }
+        Value::Object(object) => {
+
+            if !name_map {
+
+                normalize_schema(object);
+            }
+
+            for (key, value) in object.iter_mut() {
+
+                if !name_map && DATA_KEYS.contains(&key.as_str()) {
+
+                    continue;
+                }
+
+                walk(value, !name_map && NAME_MAPS.contains(&key.as_str()));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn normalize_schema(schema: &mut Map<String, Value>) {
+
+    if let Some(Value::Array(types)) = schema.get("type") {
+
+        let nullable = types.iter().any(|typ| typ == "null");
+
+        let mut types: Vec<Value> = types.iter().filter(|typ| *typ != "null").cloned().collect();
+
+        match types.len() {
+            0 => {
+
+                schema.remove("type");
+            }
+            1 => {
+
+                schema.insert("type".to_string(), types.remove(0));
+            }
+            _ => {
+
+                schema.remove("type");
+
+                let parts = types
+                    .into_iter()
+                    .map(|typ| serde_json::json!({ "type": typ }))
+                    .collect();
+
+                schema.insert("anyOf".to_string(), Value::Array(parts));
+            }
+        }
+
+        if nullable {
+
+            schema.insert("nullable".to_string(), true.into());
+        }
+    }
+
+    for key in ["oneOf", "anyOf"] {
+
+        let Some(Value::Array(parts)) = schema.get_mut(key) else {
+
+            continue;
+        };
+
+        let count = parts.len();
+
+        parts.retain(|part| part.get("type").and_then(Value::as_str) != Some("null"));
+
+        if parts.len() != count {
+
+            schema.insert("nullable".to_string(), true.into());
+        }
+    }
+
+    if let Some(value) = schema.remove("const") {
+
+        if !schema.contains_key("type") {
+
+            let typ = match &value {
+                Value::String(_) => Some("string"),
+                Value::Number(number) if number.is_f64() => Some("number"),
+                Value::Number(_) => Some("integer"),
+                Value::Bool(_) => Some("boolean"),
+                _ => None,
+            };
+
+            if let Some(typ) = typ {
+
+                schema.insert("type".to_string(), typ.into());
+            }
+        }
+
+        schema.insert("enum".to_string(), Value::Array(vec![value]));
+    }
+
+    for (exclusive, bound) in [
+        ("exclusiveMinimum", "minimum"),
+        ("exclusiveMaximum", "maximum"),
+    ] {
+
+        if let Some(value @ Value::Number(_)) = schema.remove(exclusive) {
+
+            schema.insert(bound.to_string(), value);
+
+            schema.insert(exclusive.to_string(), true.into());
+        }
+    }
+
+    if let Some(Value::Array(examples)) = schema.remove("examples")
+        && let Some(example) = examples.into_iter().next()
+    {
+
+        schema.insert("example".to_string(), example);
+    }
+
+    if schema.get("contentEncoding").and_then(Value::as_str) == Some("base64")
+        && !schema.contains_key("format")
+    {
+
+        schema.insert("format".to_string(), "byte".into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use serde_json::json;
+
+    #[test]
+
+    fn test_normalize_openapi_3_1() {
+
+        let mut spec = json!({
+            "openapi": "3.1.0",
+            "webhooks": {},
+            "components": {
+                "schemas": {
+                    "Pet": {
+                        "type": "object",
+                        "properties": {
+                            "const": { "type": ["string", "null"] },
+                            "kind": { "const": "pet" },
+                            "owner": { "anyOf": [{ "$ref": "#/components/schemas/Owner" }, { "type": "null" }] },
+                            "age": { "type": "integer", "exclusiveMinimum": 0 }
+                        }
+                    }
+                }
+            }
+        });
+
+        assert!(super::is_openapi_3_1(&spec));
+
+        super::normalize_openapi_3_1(&mut spec);
+
+        assert_eq!(
+            spec,
+            json!({
+                "openapi": "3.0.3",
+                "paths": {},
+                "components": {
+                    "schemas": {
+                        "Pet": {
+                            "type": "object",
+                            "properties": {
+                                "const": { "type": "string", "nullable": true },
+                                "kind": { "type": "string", "enum": ["pet"] },
+                                "owner": { "anyOf": [{ "$ref": "#/components/schemas/Owner" }], "nullable": true },
+                                "age": { "type": "integer", "minimum": 0, "exclusiveMinimum": true }
+                            }
+                        }
+                    }
+                }
+            })
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use std::{
+    collections::BTreeMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+use similar::TextDiff;
+
+/// Where `CrateWriter` puts the crate. Paths are relative to the crate root.
+pub trait Output {
+    /// Contents of a file, `None` when it does not exist.
+    fn read(&self, path: &Path) -> io::Result<Option<String>>;
+
+    /// Writes a file, creating its parent directories.
+    fn write(&mut self, path: &Path, contents: String) -> io::Result<()>;
+
+    fn remove(&mut self, path: &Path) -> io::Result<()>;
+
+    /// Files directly inside `dir`.
+    fn list(&self, dir: &Path) -> io::Result<Vec<PathBuf>>;
+}
+
+/// Output into a directory on disk.
+pub struct FileSystemOutput {
+    root: PathBuf,
+}
+
+impl FileSystemOutput {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+
+        Self { root: root.into() }
+    }
+}
+
+impl Output for FileSystemOutput {
+    fn read(&self, path: &Path) -> io::Result<Option<String>> {
+
+        match std::fs::read_to_string(self.root.join(path)) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn write(&mut self, path: &Path, contents: String) -> io::Result<()> {
+
+        let path = self.root.join(path);
+
+        if let Some(parent) = path.parent() {
+
+            std::fs::create_dir_all(parent)?;
+        }
+
+        std::fs::write(path, contents)
+    }
+
+    fn remove(&mut self, path: &Path) -> io::Result<()> {
+
+        std::fs::remove_file(self.root.join(path))
+    }
+
+    fn list(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+
+        let entries = match std::fs::read_dir(self.root.join(dir)) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut files = Vec::new();
+
+        for entry in entries {
+
+            let entry = entry?;
+
+            if entry.file_type()?.is_file() {
+
+                files.push(dir.join(entry.file_name()));
+            }
+        }
+
+        Ok(files)
+    }
+}
+
+/// Output kept in memory, on top of an optional directory on disk that is
+/// read but never modified. Writing into it shows what a regeneration
+/// would change.
+#[derive(Default)]
+pub struct MemoryOutput {
+    base: Option<FileSystemOutput>,
+    /// Written files, or `None` for removed ones.
+    changes: BTreeMap<PathBuf, Option<String>>,
+}
+
+impl MemoryOutput {
+    pub fn new() -> Self {
+
+        Self::default()
+    }
+
+    /// Reads files not written yet from `root`.
+    pub fn over(root: impl Into<PathBuf>) -> Self {
+
+        Self {
+            base: Some(FileSystemOutput::new(root)),
+            changes: BTreeMap::new(),
+        }
+    }
+
+    /// Files as they are after the writes, by path.
+    pub fn files(&self) -> BTreeMap<PathBuf, String> {
+
+        self.changes
+            .iter()
+            .filter_map(|(path, contents)| Some((path.clone(), contents.clone()?)))
+            .collect()
+    }
+
+    /// Unified diffs of the files that differ from the base directory, in
+    /// path order.
+    pub fn diff(&self) -> io::Result<Vec<FileDiff>> {
+
+        let mut diffs = Vec::new();
+
+        for (path, contents) in &self.changes {
+
+            let old = match &self.base {
+                Some(base) => base.read(path)?,
+                None => None,
+            };
+
+            if old == *contents {
+
+                continue;
+            }
+
+            let name = path.display().to_string();
+
+            let old_name = if old.is_some() {
+
+                format!("a/{name}")
+            } else {
+
+                "/dev/null".to_string()
+            };
+
+            let new_name = if contents.is_some() {
+
+                format!("b/{name}")
+            } else {
+
+                "/dev/null".to_string()
+            };
+
+            let text_diff = TextDiff::from_lines(
+                old.as_deref().unwrap_or_default(),
+                contents.as_deref().unwrap_or_default(),
+            );
+
+            // written even without hunks, e.g. for a removed empty file
+            let mut diff = format!("--- {old_name}\n+++ {new_name}\n");
+
+            for hunk in text_diff.unified_diff().iter_hunks() {
+
+                diff.push_str(&hunk.to_string());
+            }
+
+            diffs.push(FileDiff {
+                path: path.clone(),
+                diff,
+            });
+        }
+
+        Ok(diffs)
+    }
+}
+
+impl Output for MemoryOutput {
+    fn read(&self, path: &Path) -> io::Result<Option<String>> {
+
+        match (self.changes.get(path), &self.base) {
+            (Some(contents), _) => Ok(contents.clone()),
+            (None, Some(base)) => base.read(path),
+            (None, None) => Ok(None),
+        }
+    }
+
+    fn write(&mut self, path: &Path, contents: String) -> io::Result<()> {
+
+        self.changes.insert(path.to_path_buf(), Some(contents));
+
+        Ok(())
+    }
+
+    fn remove(&mut self, path: &Path) -> io::Result<()> {
+
+        if self.read(path)?.is_none() {
+
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} does not exist", path.display()),
+            ));
+        }
+
+        self.changes.insert(path.to_path_buf(), None);
+
+        Ok(())
+    }
+
+    fn list(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+
+        let mut files = match &self.base {
+            Some(base) => base.list(dir)?,
+            None => Vec::new(),
+        };
+
+        for (path, contents) in &self.changes {
+
+            if path.parent() == Some(dir) && !files.contains(path) {
+
+                files.push(path.clone());
+            }
+
+            if contents.is_none() {
+
+                files.retain(|file| file != path);
+            }
+        }
+
+        Ok(files)
+    }
+}
+
+/// A file that differs from the one on disk.
+#[derive(Debug, Clone)]
+pub struct FileDiff {
+    pub path: PathBuf,
+    /// Unified diff from the file on disk to the generated one.
+    pub diff: String,
+}
@@ -1,156 +1,386 @@
-use super::create_rust_safe_ident;
-use heck::ToSnakeCase;
+use super::{context::Context, create_rust_safe_ident, types::mapped_type};
+use heck::{ToPascalCase, ToSnakeCase};
 use openapiv3::ParameterSchemaOrContent::Content;
 use openapiv3::ParameterSchemaOrContent::Schema;
-use openapiv3::{Parameter, ReferenceOr};
-use proc_macro2::{Ident, TokenStream};
+use openapiv3::{
+    Parameter, ParameterData, ReferenceOr, SchemaKind, StringFormat, StringType, Type,
+    VariantOrUnknownOrEmpty,
+};
+use proc_macro2::TokenStream;
 use quote::format_ident;
 use quote::quote;
 
 pub struct ParamsGenerator<'a> {
-    name: &'a Ident,
+    ctx: &'a Context,
+    name: &'a str,
     params: &'a [ReferenceOr<Parameter>],
 }
 
-type ParamStruct = TokenStream;
-
-type ParamsInPathFields = TokenStream;
+/// Per-location output of [`ParamsGenerator`].
+///
+/// `query`, `headers` and `cookies` hold a full struct definition, or nothing when the
+/// operation declares no parameters in that location.
+#[derive(Default)]
+pub struct GeneratedParams {
+    pub query: TokenStream,
+    pub headers: TokenStream,
+    pub cookies: TokenStream,
+    pub in_path_fields: TokenStream,
+    pub replace_fields: TokenStream,
+}
 
-type ParamsReplaceFields = TokenStream;
+/// Header parameters the OpenAPI spec says must be ignored, they are driven by
+/// the request body and the client instead.
+const IGNORED_HEADERS: [&str; 3] = ["accept", "content-type", "authorization"];
 
 impl<'a> ParamsGenerator<'a> {
-    pub fn new(name: &'a Ident, params: &'a [ReferenceOr<Parameter>]) -> Self {
+    pub fn new(ctx: &'a Context, name: &'a str, params: &'a [ReferenceOr<Parameter>]) -> Self {
 
-        Self { name, params }
+        Self { ctx, name, params }
     }
 
-    pub fn generate(
-        &self,
-    ) -> Result<(ParamStruct, ParamsInPathFields, ParamsReplaceFields), String> {
+    pub fn generate(&self) -> Result<GeneratedParams, String> {
+
+        let mut output = GeneratedParams::default();
 
         let mut fields = TokenStream::new();
 
-        let mut in_path_fields = TokenStream::new();
+        let mut header_fields = TokenStream::new();
 
-        let mut replace_fields = TokenStream::new();
+        let mut cookie_fields = TokenStream::new();
 
-        let struct_ident = self.name;
+        for param in self.params {
 
-        for params in self.params {
+            match self.ctx.parameter(param)? {
+                Parameter::Query { parameter_data, .. } => {
 
-            let ReferenceOr::Item(param) = params else {
+                    let field_name = &parameter_data.name;
 
-                continue;
-            };
+                    let serde_name = field_name;
 
-            if let openapiv3::Parameter::Query { parameter_data, .. } = &param {
+                    let field_ident = create_rust_safe_ident(&field_name.to_snake_case());
 
-                let field_name = &parameter_data.name;
+                    let field_type = param_type(
+                        self.ctx,
+                        &format!(
+                            "{}Params{}",
+                            self.name,
+                            parameter_data.name.to_pascal_case()
+                        ),
+                        parameter_data,
+                    )?;
 
-                let serde_name = field_name;
+                    let field_type = if nullable(parameter_data) {
 
-                let field_ident = create_rust_safe_ident(&field_name.to_snake_case());
+                        quote! { Option<#field_type> }
+                    } else {
 
-                let field_type = match &parameter_data.format {
-                    Schema(schema) => {
+                        field_type
+                    };
 
-                        let openapiv3::ReferenceOr::Item(schema) = schema else {
+                    let doc_comment =
+                        super::DocsGenerator::generate(parameter_data.description.as_deref());
 
-                            // params with references are not supported in this context
-                            return Err("Unsupported parameter schema".to_string());
-                        };
+                    fields.extend(quote! {
+                        #doc_comment
+                        #[serde(rename = #serde_name)]
+                        pub #field_ident: #field_type,
+                    });
+                }
+                Parameter::Path { parameter_data, .. } => {
 
-                        let rust_type = super::TypesGenerator::new(schema).generate()?;
+                    if has_no_display(self.ctx, parameter_data)? {
 
-                        if schema.schema_data.nullable {
+                        return Err(format!(
+                            "Path parameter {} of format byte or binary is not supported",
+                            parameter_data.name
+                        ));
+                    }
 
-                            quote! { Option<#rust_type> }
-                        } else {
+                    let field_name = create_rust_safe_ident(&parameter_data.name.to_snake_case());
 
-                            quote! { #rust_type }
-                        }
-                    }
-                    Content(_) => {
+                    let field_type = param_type(
+                        self.ctx,
+                        &format!("{}{}", self.name, parameter_data.name.to_pascal_case()),
+                        parameter_data,
+                    )?;
 
-                        return Err("Content parameters are not supported".to_string());
-                    }
-                };
+                    let field_type = if nullable(parameter_data) {
 
-                let doc_comment =
-                    super::DocsGenerator::generate(parameter_data.description.as_deref());
+                        quote! { Option<#field_type> }
+                    } else {
 
-                fields.extend(quote! {
-                    #doc_comment
-                    #[serde(rename = #serde_name)]
-                    pub #field_ident: #field_type,
-                });
-            } else if let openapiv3::Parameter::Path { parameter_data, .. } = &param {
+                        field_type
+                    };
 
-                let field_name = &parameter_data.name;
+                    let doc_comment =
+                        super::DocsGenerator::generate(parameter_data.description.as_deref());
 
-                let field_ident = format_ident!("{}", field_name);
+                    output.in_path_fields.extend(quote! {
+                        #doc_comment
+                        pub #field_name: #field_type,
+                    });
 
-                let field_name = create_rust_safe_ident(&field_name.to_snake_case());
+                    let replace_ident = format!("{{{}}}", parameter_data.name);
 
-                let field_type = match &parameter_data.format {
-                    Schema(schema) => {
+                    output.replace_fields.extend(quote! {
+                        .replace(
+                            #replace_ident,
+                            &self.#field_name.to_string()
+                        )
+                    });
+                }
+                Parameter::Header { parameter_data, .. } => {
 
-                        let openapiv3::ReferenceOr::Item(schema) = schema else {
+                    let header_name = &parameter_data.name;
 
-                            // params with references are not supported in this context
-                            return Err("Unsupported parameter schema".to_string());
-                        };
+                    if IGNORED_HEADERS.contains(&header_name.to_lowercase().as_str()) {
 
-                        let rust_type = super::TypesGenerator::new(schema).generate()?;
+                        continue;
+                    }
 
-                        if schema.schema_data.nullable {
+                    let field_ident = create_rust_safe_ident(&header_name.to_snake_case());
 
-                            quote! { Option<#rust_type> }
-                        } else {
+                    let field_type = param_type(
+                        self.ctx,
+                        &format!(
+                            "{}Headers{}",
+                            self.name,
+                            parameter_data.name.to_pascal_case()
+                        ),
+                        parameter_data,
+                    )?;
 
-                            quote! { #rust_type }
-                        }
-                    }
-                    Content(_) => {
+                    let field_type = if !parameter_data.required || nullable(parameter_data) {
 
-                        return Err("Content parameters are not supported".to_string());
-                    }
-                };
+                        quote! { Option<#field_type> }
+                    } else {
+
+                        field_type
+                    };
+
+                    let doc_comment =
+                        super::DocsGenerator::generate(parameter_data.description.as_deref());
+
+                    header_fields.extend(quote! {
+                        #doc_comment
+                        #[serde(rename = #header_name)]
+                        pub #field_ident: #field_type,
+                    });
+                }
+                Parameter::Cookie { parameter_data, .. } => {
+
+                    let cookie_name = &parameter_data.name;
+
+                    let field_ident = create_rust_safe_ident(&cookie_name.to_snake_case());
+
+                    let field_type = param_type(
+                        self.ctx,
+                        &format!(
+                            "{}Cookies{}",
+                            self.name,
+                            parameter_data.name.to_pascal_case()
+                        ),
+                        parameter_data,
+                    )?;
+
+                    let field_type = if !parameter_data.required || nullable(parameter_data) {
 
-                let doc_comment =
-                    super::DocsGenerator::generate(parameter_data.description.as_deref());
+                        quote! { Option<#field_type> }
+                    } else {
 
-                in_path_fields.extend(quote! {
-                    #doc_comment
-                    pub #field_name: #field_type,
-                });
+                        field_type
+                    };
 
-                let replace_ident = format!("{{{field_ident}}}");
+                    let doc_comment =
+                        super::DocsGenerator::generate(parameter_data.description.as_deref());
 
-                replace_fields.extend(quote! {
-                    .replace(
-                        #replace_ident,
-                        &self.#field_name.to_string()
-                    )
-                });
+                    cookie_fields.extend(quote! {
+                        #doc_comment
+                        #[serde(rename = #cookie_name)]
+                        pub #field_ident: #field_type,
+                    });
+                }
             }
         }
 
-        if fields.is_empty() {
+        if !fields.is_empty() {
 
-            Ok((quote! {}, in_path_fields, replace_fields))
-        } else {
+            let struct_ident = format_ident!("{}Params", self.name);
 
-            Ok((
-                quote! {
-                    #[derive(Debug, Clone, Serialize, Deserialize)]
-                    pub struct #struct_ident {
-                        #fields
-                    }
-                },
-                in_path_fields,
-                replace_fields,
-            ))
+            output.query = quote! {
+                #[derive(Debug, Clone, Serialize, Deserialize)]
+                pub struct #struct_ident {
+                    #fields
+                }
+            };
         }
+
+        if !header_fields.is_empty() {
+
+            let struct_ident = format_ident!("{}Headers", self.name);
+
+            output.headers = quote! {
+                #[derive(Debug, Clone, Serialize, Deserialize)]
+                pub struct #struct_ident {
+                    #header_fields
+                }
+            };
+        }
+
+        if !cookie_fields.is_empty() {
+
+            let struct_ident = format_ident!("{}Cookies", self.name);
+
+            output.cookies = quote! {
+                #[derive(Debug, Clone, Serialize, Deserialize)]
+                pub struct #struct_ident {
+                    #cookie_fields
+                }
+            };
+        }
+
+        Ok(output)
+    }
+}
+
+fn nullable(parameter_data: &ParameterData) -> bool {
+
+    matches!(
+        &parameter_data.format,
+        Schema(ReferenceOr::Item(schema)) if schema.schema_data.nullable
+    )
+}
+
+/// Whether the parameter maps to `Base64` or `bytes::Bytes`, which have no
+/// `Display` to build the path with.
+fn has_no_display(ctx: &Context, parameter_data: &ParameterData) -> Result<bool, String> {
+
+    let Schema(schema) = &parameter_data.format else {
+
+        return Ok(false);
+    };
+
+    let schema = ctx.schema(schema)?;
+
+    if mapped_type(ctx, schema).is_some() {
+
+        return Ok(false);
+    }
+
+    Ok(matches!(
+        &schema.schema_kind,
+        SchemaKind::Type(Type::String(StringType {
+            format: VariantOrUnknownOrEmpty::Item(StringFormat::Byte | StringFormat::Binary),
+            ..
+        }))
+    ))
+}
+
+fn param_type(
+    ctx: &Context,
+    name: &str,
+    parameter_data: &ParameterData,
+) -> Result<TokenStream, String> {
+
+    match &parameter_data.format {
+        Schema(schema) => super::schema_ref_type(ctx, name, schema),
+        Content(_) => Err("Content parameters are not supported".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    #[test]
+
+    fn test_headers() {
+
+        let params: Vec<openapiv3::ReferenceOr<openapiv3::Parameter>> = serde_json::from_str(
+            r#"[
+                { "in": "header", "name": "X-Request-Id", "required": true, "schema": { "type": "string" } },
+                { "in": "header", "name": "X-Page", "schema": { "type": "integer", "format": "int32" } },
+                { "in": "header", "name": "Accept", "schema": { "type": "string" } },
+                { "in": "header", "name": "content-type", "schema": { "type": "string" } },
+                { "in": "header", "name": "Authorization", "required": true, "schema": { "type": "string" } }
+            ]"#,
+        )
+        .unwrap();
+
+        let ctx = crate::Context::new();
+
+        let generated = super::ParamsGenerator::new(&ctx, "ListPets", &params)
+            .generate()
+            .unwrap();
+
+        assert_eq!(
+            crate::format_tokens(&generated.headers),
+            r#"#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListPetsHeaders {
+    #[serde(rename = "X-Request-Id")]
+    pub x_request_id: String,
+    #[serde(rename = "X-Page")]
+    pub x_page: Option<i32>,
+}
+"#
+        );
+
+        assert!(generated.query.is_empty());
+    }
+
+    #[test]
+
+    fn test_cookies() {
+
+        let params: Vec<openapiv3::ReferenceOr<openapiv3::Parameter>> = serde_json::from_str(
+            r#"[
+                { "in": "cookie", "name": "session_id", "required": true, "schema": { "type": "string" } },
+                { "in": "cookie", "name": "Theme", "schema": { "type": "string" } }
+            ]"#,
+        )
+        .unwrap();
+
+        let ctx = crate::Context::new();
+
+        let generated = super::ParamsGenerator::new(&ctx, "ListPets", &params)
+            .generate()
+            .unwrap();
+
+        assert_eq!(
+            crate::format_tokens(&generated.cookies),
+            r#"#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListPetsCookies {
+    #[serde(rename = "session_id")]
+    pub session_id: String,
+    #[serde(rename = "Theme")]
+    pub theme: Option<String>,
+}
+"#
+        );
+
+        assert!(generated.headers.is_empty());
+    }
+
+    #[test]
+
+    fn test_byte_path_parameter() {
+
+        let params: Vec<openapiv3::ReferenceOr<openapiv3::Parameter>> = serde_json::from_str(
+            r#"[
+                { "in": "path", "name": "key", "required": true, "schema": { "type": "string", "format": "byte" } }
+            ]"#,
+        )
+        .unwrap();
+
+        let ctx = crate::Context::new();
+
+        let error = super::ParamsGenerator::new(&ctx, "GetFile", &params)
+            .generate()
+            .err();
+
+        assert_eq!(
+            error.as_deref(),
+            Some("Path parameter key of format byte or binary is not supported")
+        );
     }
 }
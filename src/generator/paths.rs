@@ -1,17 +1,22 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashSet},
     fmt::{self, Formatter},
 };
 
 use heck::{ToPascalCase, ToSnakeCase};
-use openapiv3::{Operation, PathItem, ReferenceOr, SchemaKind, Type};
+use openapiv3::{Operation, Parameter, PathItem, ReferenceOr, RequestBody};
 use proc_macro2::{Ident, TokenStream};
 use quote::format_ident;
 use quote::quote;
 
-use crate::{ParamsGenerator, generator::docs::DocsGenerator};
+use crate::{
+    GeneratedParams, ParamsGenerator, ResponsesGenerator,
+    generator::{context::Context, docs::DocsGenerator, types::schema_ref_type},
+    json_pointer,
+};
 
 pub struct PathsGenerator<'a> {
+    ctx: &'a Context,
     name: &'a str,
     path_item: &'a PathItem,
 }
@@ -45,94 +50,103 @@ type OperationName = String;
 
 type OperationTurple = (OperationName, TokenStream);
 
-pub type OperationMap = HashMap<OperationName, TokenStream>;
+/// Sorted by name, so that the generated files are written in a stable order.
+pub type OperationMap = BTreeMap<OperationName, TokenStream>;
 
 impl<'a> PathsGenerator<'a> {
-    pub fn new(name: &'a str, path_item: &'a PathItem) -> Self {
+    pub fn new(ctx: &'a Context, name: &'a str, path_item: &'a PathItem) -> Self {
 
-        Self { name, path_item }
+        Self {
+            ctx,
+            name,
+            path_item,
+        }
     }
 
-    pub fn generate(&self) -> Result<OperationMap, String> {
+    /// Generates every operation of the path item. Operations that cannot be
+    /// generated are reported to the context and left out.
+    pub fn generate(&self) -> OperationMap {
 
         let mut output = OperationMap::new();
 
-        if let Some(get) = &self.path_item.get {
-
-            let (op_name, token) = self.gent(get, Method::Get)?;
-
-            output.insert(op_name, token);
-        }
-
-        if let Some(post) = &self.path_item.post {
-
-            let (op_name, token) = self.gent(post, Method::Post)?;
-
-            output.insert(op_name, token);
-        }
-
-        if let Some(put) = &self.path_item.put {
-
-            let (op_name, token) = self.gent(put, Method::Put)?;
-
-            output.insert(op_name, token);
-        }
-
-        if let Some(delete) = &self.path_item.delete {
-
-            let (op_name, token) = self.gent(delete, Method::Delete)?;
-
-            output.insert(op_name, token);
-        }
-
-        if let Some(patch) = &self.path_item.patch {
-
-            let (op_name, token) = self.gent(patch, Method::Patch)?;
-
-            output.insert(op_name, token);
-        }
+        let operations = [
+            (&self.path_item.get, Method::Get),
+            (&self.path_item.post, Method::Post),
+            (&self.path_item.put, Method::Put),
+            (&self.path_item.delete, Method::Delete),
+            (&self.path_item.patch, Method::Patch),
+            (&self.path_item.head, Method::Head),
+            (&self.path_item.options, Method::Options),
+        ];
 
-        if let Some(head) = &self.path_item.head {
+        for (op, method) in operations {
 
-            let (op_name, token) = self.gent(head, Method::Head)?;
+            let Some(op) = op else {
 
-            output.insert(op_name, token);
-        }
+                continue;
+            };
 
-        if let Some(options) = &self.path_item.options {
+            let pointer = json_pointer(&["paths", self.name, &method.to_string().to_lowercase()]);
 
-            let (op_name, token) = self.gent(options, Method::Options)?;
+            match self.gent(op, method, &pointer) {
+                Ok((op_name, token)) => {
 
-            output.insert(op_name, token);
+                    output.insert(op_name, token);
+                }
+                Err(reason) => self.ctx.report(pointer, reason),
+            }
         }
 
-        Ok(output)
+        output
     }
 
-    fn gent(&self, op: &Operation, method: Method) -> Result<OperationTurple, String> {
+    fn gent(
+        &self,
+        op: &Operation,
+        method: Method,
+        pointer: &str,
+    ) -> Result<OperationTurple, String> {
 
         if op.deprecated {
 
             return Err("Operation is deprecated".to_string());
         }
 
-        let struct_name = op
-            .operation_id
-            .as_ref()
-            .expect("Operation ID is required")
+        // drop whatever a previously failed operation left behind
+        self.ctx.take_inline_types();
+
+        let struct_name = self
+            .ctx
+            .operation_name(pointer)
+            .ok_or("Operation is not named")?
             .to_pascal_case();
 
         let struct_ident = format_ident!("{}", struct_name);
 
         let params_struct_name = format_ident!("{}Params", struct_name);
 
+        let headers_struct_name = format_ident!("{}Headers", struct_name);
+
+        let cookies_struct_name = format_ident!("{}Cookies", struct_name);
+
         let doc_comment = DocsGenerator::generate(op.description.as_deref());
 
-        let (params, inpath_fields, replace_fields) =
-            ParamsGenerator::new(&params_struct_name, &op.parameters).generate()?;
+        let GeneratedParams {
+            query,
+            headers,
+            cookies,
+            in_path_fields: inpath_fields,
+            replace_fields,
+        } = ParamsGenerator::new(self.ctx, &struct_name, &self.parameters(op)?).generate()?;
 
         let (params, param_fn, param_struct_field, param_type) =
-            params_and_fn(params, params_struct_name);
+            params_and_fn(query, params_struct_name);
+
+        let (headers, headers_fn, headers_struct_field, headers_type) =
+            map_params_and_fn(headers, headers_struct_name, "headers", "Headers");
+
+        let (cookies, cookies_fn, cookies_struct_field, cookies_type) =
+            map_params_and_fn(cookies, cookies_struct_name, "cookies", "Cookies");
 
         let path_fn = path_fn(replace_fields);
 
@@ -140,125 +154,37 @@ impl<'a> PathsGenerator<'a> {
 
         let path = self.name;
 
-        let response_name_type = {
-
-            let resp = op
-                .responses
-                .responses
-                .get(&openapiv3::StatusCode::Code(200))
-                .and_then(|ref_or| match ref_or {
-                    ReferenceOr::Reference { reference } => reference
-                        .strip_prefix("#/components/schemas/")
-                        .map(|name| (name.to_pascal_case(), false)),
-                    ReferenceOr::Item(item) => item
-                        .content
-                        .get("application/json")
-                        .or_else(|| item.content.get("application/xml"))
-                        .and_then(|mt| mt.schema.clone())
-                        .and_then(|schema_ro| match schema_ro {
-                            ReferenceOr::Reference { reference } => reference
-                                .strip_prefix("#/components/schemas/")
-                                .map(|name| (name.to_pascal_case(), false)),
-                            ReferenceOr::Item(schema) => {
-
-                                if let SchemaKind::Type(Type::Array(arr)) = &schema.schema_kind
-                                    && let Some(ReferenceOr::Reference { reference }) = &arr.items
-                                {
-
-                                    return reference
-                                        .strip_prefix("#/components/schemas/")
-                                        .map(|name| (name.to_pascal_case(), true));
-                                }
-
-                                None
-                            }
-                        }),
-                });
-
-            if let Some((name, is_array)) = resp {
+        let responses =
+            ResponsesGenerator::new(self.ctx, &struct_name, &op.responses).generate()?;
 
-                let ident = format_ident!("{}", name);
+        let response_ident = format_ident!("{}Response", struct_name);
 
-                if is_array {
+        let error_ident = format_ident!("{}Error", struct_name);
 
-                    quote! { type Response = Vec<#ident>; }
-                } else {
-
-                    quote! { type Response = #ident; }
-                }
-            } else {
-
-                quote! { type Response = (); }
-            }
+        let (body_ty_ts, form) = match &op.request_body {
+            Some(request_body) => self.request_body(request_body, &struct_name)?,
+            None => (None, false),
         };
 
-        let body_ty_ts: Option<proc_macro2::TokenStream> =
-            op.request_body
-                .as_ref()
-                .and_then(|schema_ro| match schema_ro {
-                    openapiv3::ReferenceOr::Reference { reference } => {
-                        reference.strip_prefix("#/components/schemas/").map(|name| {
-
-                            let ident = format_ident!("{}", name.to_pascal_case());
-
-                            quote! { #ident }
-                        })
-                    }
-
-                    openapiv3::ReferenceOr::Item(schema) => {
-
-                        let schema = schema
-                            .content
-                            .get("application/json")
-                            .or_else(|| schema.content.get("application/xml"))
-                            .and_then(|mt| mt.schema.clone())?;
-
-                        match schema {
-                            ReferenceOr::Item(item) => {
+        let form_const = if form {
 
-                                if let openapiv3::SchemaKind::Type(openapiv3::Type::Array(arr)) =
-                                    &item.schema_kind
-                                    && let Some(openapiv3::ReferenceOr::Reference { reference }) =
-                                        &arr.items
-                                {
+            quote! { const FORM: bool = true; }
+        } else {
 
-                                    return reference.strip_prefix("#/components/schemas/").map(
-                                        |name| {
-
-                                            let ident = format_ident!("{}", name.to_pascal_case());
-
-                                            quote! { Vec<#ident> }
-                                        },
-                                    );
-                                }
-                            }
-                            ReferenceOr::Reference { reference } => {
-
-                                return reference.strip_prefix("#/components/schemas/").map(
-                                    |name| {
-
-                                        let ident = format_ident!("{}", name.to_pascal_case());
-
-                                        quote! { #ident }
-                                    },
-                                );
-                            }
-                        }
-
-                        None
-                    }
-                });
+            quote! {}
+        };
 
         let (body, body_fn, body_type) = body_and_fn_ts(body_ty_ts);
 
-        // TODO: Headers and Cookies
+        let inline_types = self.ctx.take_inline_types();
+
         Ok((
             struct_name.to_snake_case(),
             quote! {
-                use crate::Request;
-                use reqwest::Method;
+                use reqwest::{Method, StatusCode};
                 use serde::{Deserialize, Serialize};
                 use std::borrow::Cow;
+                use std::collections::HashMap;
                 use super::*;
 
                 #doc_comment
@@ -267,27 +193,123 @@ impl<'a> PathsGenerator<'a> {
                     #inpath_fields
                     #body
                     #param_struct_field
+                    #headers_struct_field
+                    #cookies_struct_field
                 }
 
                 #params
 
+                #headers
+
+                #cookies
+
+                #responses
+
+                #inline_types
+
                 impl Request for #struct_ident {
-                    #response_name_type
+                    type Response = #response_ident;
+                    type Error = #error_ident;
                     #body_type
                     #param_type
+                    #headers_type
+                    #cookies_type
 
                     const METHOD: Method = Method::#method_ident;
                     const PATH: &'static str = #path;
+                    #form_const
 
                     #body_fn
 
                     #param_fn
 
+                    #headers_fn
+
+                    #cookies_fn
+
                     #path_fn
                 }
             },
         ))
     }
+
+    /// Rust type of the request body, and whether it is sent as a form
+    /// rather than JSON. Bodies in other media types, such as
+    /// `multipart/form-data`, cannot be sent and fail the operation.
+    fn request_body(
+        &self,
+        request_body: &ReferenceOr<RequestBody>,
+        struct_name: &str,
+    ) -> Result<(Option<TokenStream>, bool), String> {
+
+        let content = &self.ctx.request_body(request_body)?.content;
+
+        let (media_type, form) = match content
+            .get("application/json")
+            .or_else(|| content.get("application/xml"))
+        {
+            Some(media_type) => (media_type, false),
+            None => match content.get("application/x-www-form-urlencoded") {
+                Some(media_type) => (media_type, true),
+                None if content.is_empty() => return Ok((None, false)),
+                None => {
+
+                    let media_types: Vec<&str> = content.keys().map(String::as_str).collect();
+
+                    return Err(format!(
+                        "Unsupported request body media type {}",
+                        media_types.join(", ")
+                    ));
+                }
+            },
+        };
+
+        let body_type = media_type
+            .schema
+            .as_ref()
+            .map(|schema| schema_ref_type(self.ctx, &format!("{struct_name}Body"), schema))
+            .transpose()?;
+
+        Ok((body_type, form))
+    }
+
+    /// Parameters of `op` along with those shared by the whole path, which
+    /// `op` overrides when it declares one with the same name and location.
+    fn parameters(&self, op: &Operation) -> Result<Vec<ReferenceOr<Parameter>>, String> {
+
+        let mut overridden = HashSet::new();
+
+        for param in &op.parameters {
+
+            overridden.insert(parameter_key(self.ctx.parameter(param)?));
+        }
+
+        let mut parameters = Vec::new();
+
+        for param in &self.path_item.parameters {
+
+            if !overridden.contains(&parameter_key(self.ctx.parameter(param)?)) {
+
+                parameters.push(param.clone());
+            }
+        }
+
+        parameters.extend(op.parameters.iter().cloned());
+
+        Ok(parameters)
+    }
+}
+
+fn parameter_key(param: &Parameter) -> (&str, &'static str) {
+
+    let location = match param {
+        Parameter::Query { .. } => "query",
+        Parameter::Header { .. } => "header",
+        Parameter::Path { .. } => "path",
+        Parameter::Cookie { .. } => "cookie",
+    };
+
+    (param.parameter_data_ref().name.as_str(), location)
 }
 
 type ParamStruct = TokenStream;
@@ -336,59 +358,80 @@ pub fn params_and_fn(
     )
 }
 
-type PathFn = TokenStream;
+type MapParamsStruct = TokenStream;
 
-pub fn path_fn(replace_fields: TokenStream) -> PathFn {
-
-    if replace_fields.is_empty() {
-
-        return quote! {};
-    }
-
-    quote! {
-        fn path(&self) -> Cow<'static, str> {
-            let path = Self::PATH
-                #replace_fields;
-
-            Cow::Owned(path)
-        }
-    }
-}
+type MapParamsFn = TokenStream;
 
-type BodyInStructField = TokenStream;
+type MapParamsFieldInStruct = TokenStream;
 
-type BodyFn = TokenStream;
+type MapParamsTypeRequest = TokenStream;
 
-type BodyTypeRequest = TokenStream;
+/// Struct field, accessor and associated type of the parameters sent as a
+/// name/value map, `field` being `headers` or `cookies` and `type_name` the
+/// matching `Request` associated type.
+pub fn map_params_and_fn(
+    params: TokenStream,
+    struct_name: Ident,
+    field: &str,
+    type_name: &str,
+) -> (
+    MapParamsStruct,
+    MapParamsFn,
+    MapParamsFieldInStruct,
+    MapParamsTypeRequest,
+) {
 
-pub fn body_and_fn(struct_name: Option<Ident>) -> (BodyInStructField, BodyFn, BodyTypeRequest) {
+    let type_ident = format_ident!("{}", type_name);
 
-    let Some(struct_name) = struct_name else {
+    if params.is_empty() {
 
         return (
+            quote! {},
             quote! {},
             quote! {},
             quote! {
-                type Body = ();
+                type #type_ident = ();
             },
         );
     };
 
+    let field_ident = format_ident!("{}", field);
+
     (
+        params,
         quote! {
-            pub body: #struct_name,
+            fn #field_ident(&self) -> Option<&Self::#type_ident> {
+                Some(&self.#field_ident)
+            }
         },
         quote! {
-            fn body(&self) -> Option<&Self::Body> {
-                Some(&self.body)
-            }
+            pub #field_ident: #struct_name,
         },
         quote! {
-            type Body = #struct_name;
+            type #type_ident = #struct_name;
         },
     )
 }
 
+type PathFn = TokenStream;
+
+pub fn path_fn(replace_fields: TokenStream) -> PathFn {
+
+    if replace_fields.is_empty() {
+
+        return quote! {};
+    }
+
+    quote! {
+        fn path(&self) -> Cow<'static, str> {
+            let path = Self::PATH
+                #replace_fields;
+
+            Cow::Owned(path)
+        }
+    }
+}
+
 fn body_and_fn_ts(ty: Option<proc_macro2::TokenStream>) -> (TokenStream, TokenStream, TokenStream) {
 
     let field = if let Some(ref ts) = ty {
@@ -421,3 +464,118 @@ fn body_and_fn_ts(ty: Option<proc_macro2::TokenStream>) -> (TokenStream, TokenSt
 
     (field, fn_body, ty_decl)
 }
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{OpenAPIGenerator, format_tokens};
+
+    #[test]
+
+    fn test_path_level_parameters() {
+
+        let generator = OpenAPIGenerator::from_json(
+            r#"{
+                "openapi": "3.0.3",
+                "info": { "title": "t", "version": "1" },
+                "paths": {
+                    "/items/{Id}": {
+                        "parameters": [
+                            { "in": "path", "name": "Id", "required": true, "schema": { "type": "integer" } },
+                            { "in": "query", "name": "Fields", "schema": { "type": "string" } }
+                        ],
+                        "get": { "operationId": "getItem", "responses": {} },
+                        "delete": {
+                            "operationId": "deleteItem",
+                            "parameters": [
+                                { "in": "path", "name": "Id", "required": true, "schema": { "type": "string" } }
+                            ],
+                            "responses": {}
+                        }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let methods = generator.gen_methods();
+
+        let get = format_tokens(&methods["get_item"]);
+
+        assert!(get.contains("pub id: i32,"));
+
+        assert!(get.contains(r#"Self::PATH.replace("{Id}", &self.id.to_string())"#));
+
+        assert!(get.contains("pub params: GetItemParams,"));
+
+        let delete = format_tokens(&methods["delete_item"]);
+
+        assert_eq!(delete.matches("pub id:").count(), 1);
+
+        assert!(delete.contains("pub id: String,"));
+
+        assert!(delete.contains(r#"Self::PATH.replace("{Id}", &self.id.to_string())"#));
+
+        assert!(delete.contains("pub params: DeleteItemParams,"));
+    }
+
+    #[test]
+
+    fn test_component_references() {
+
+        let generator = OpenAPIGenerator::from_json(
+            r##"{
+                "openapi": "3.0.3",
+                "info": { "title": "t", "version": "1" },
+                "paths": {
+                    "/items": {
+                        "post": {
+                            "operationId": "addItem",
+                            "parameters": [{ "$ref": "#/components/parameters/Limit" }],
+                            "requestBody": { "$ref": "#/components/requestBodies/Item" },
+                            "responses": { "200": { "$ref": "#/components/responses/Item" } }
+                        }
+                    }
+                },
+                "components": {
+                    "schemas": {
+                        "Item": { "type": "object", "properties": { "name": { "type": "string" } } }
+                    },
+                    "parameters": {
+                        "Limit": { "$ref": "#/components/parameters/PageLimit" },
+                        "PageLimit": { "in": "query", "name": "limit", "schema": { "type": "integer" } }
+                    },
+                    "requestBodies": {
+                        "Item": {
+                            "required": true,
+                            "content": {
+                                "application/json": { "schema": { "$ref": "#/components/schemas/Item" } }
+                            }
+                        }
+                    },
+                    "responses": {
+                        "Item": {
+                            "description": "ok",
+                            "content": {
+                                "application/json": { "schema": { "$ref": "#/components/schemas/Item" } }
+                            }
+                        }
+                    }
+                }
+            }"##,
+        )
+        .unwrap();
+
+        let add = generator.gen_methods()["add_item"].to_string();
+
+        assert!(
+            add.contains("pub struct AddItem { pub body : Item , pub params : AddItemParams , }")
+        );
+
+        assert!(add.contains(
+            "pub struct AddItemParams { # [serde (rename = \"limit\")] pub limit : i32 , }"
+        ));
+
+        assert!(add.contains("pub enum AddItemResponse { # [doc = \"ok\"] Status200 (Item) , }"));
+    }
+}
@@ -0,0 +1,383 @@
+use openapiv3::{ReferenceOr, Response, Responses, StatusCode};
+use proc_macro2::{Ident, Literal, TokenStream};
+use quote::{format_ident, quote};
+
+use crate::generator::{context::Context, docs::DocsGenerator, types::schema_ref_type};
+
+pub struct ResponsesGenerator<'a> {
+    ctx: &'a Context,
+    name: &'a str,
+    responses: &'a Responses,
+}
+
+impl<'a> ResponsesGenerator<'a> {
+    pub fn new(ctx: &'a Context, name: &'a str, responses: &'a Responses) -> Self {
+
+        Self {
+            ctx,
+            name,
+            responses,
+        }
+    }
+
+    /// Generates `{name}Response` and `{name}Error`, enums with one variant
+    /// per declared status code, along with the `FromResponse` impls picking
+    /// the variant from the actual HTTP status.
+    ///
+    /// 4xx and 5xx statuses go to the error enum. `default` does too, unless
+    /// the operation declares no success status at all.
+    pub fn generate(&self) -> Result<TokenStream, String> {
+
+        let mut success = StatusEnum::default();
+
+        let mut failure = StatusEnum::default();
+
+        for (status, response) in &self.responses.responses {
+
+            let (variant_ident, pattern, is_range) = match status {
+                StatusCode::Code(code) => {
+
+                    let pattern = Literal::u16_unsuffixed(*code);
+
+                    (format_ident!("Status{}", code), quote! { #pattern }, false)
+                }
+                StatusCode::Range(range) => {
+
+                    let start = Literal::u16_unsuffixed(range * 100);
+
+                    let end = Literal::u16_unsuffixed(range * 100 + 99);
+
+                    (
+                        format_ident!("Status{}XX", range),
+                        quote! { #start..=#end },
+                        true,
+                    )
+                }
+            };
+
+            let target = if is_error_status(status) {
+
+                &mut failure
+            } else {
+
+                &mut success
+            };
+
+            let variant = Variant {
+                ident: &variant_ident,
+                type_name: &format!("{}{}", self.name, variant_ident),
+                pattern: Some(pattern),
+                is_range,
+            };
+
+            target.push(self.ctx, variant, response)?;
+        }
+
+        if let Some(response) = &self.responses.default {
+
+            let target = if success.variants.is_empty() {
+
+                &mut success
+            } else {
+
+                &mut failure
+            };
+
+            let variant = Variant {
+                ident: &format_ident!("Default"),
+                type_name: &format!("{}Default", self.name),
+                pattern: None,
+                is_range: false,
+            };
+
+            target.push(self.ctx, variant, response)?;
+        }
+
+        let response = success.generate(&format_ident!("{}Response", self.name));
+
+        let error = failure.generate(&format_ident!("{}Error", self.name));
+
+        Ok(quote! {
+            #response
+
+            #error
+        })
+    }
+}
+
+fn is_error_status(status: &StatusCode) -> bool {
+
+    match status {
+        StatusCode::Code(code) => *code >= 400,
+        StatusCode::Range(range) => *range >= 4,
+    }
+}
+
+struct Variant<'a> {
+    ident: &'a Ident,
+    /// Name of the nested type emitted for an inline schema.
+    type_name: &'a str,
+    /// `None` for the `default` response.
+    pattern: Option<TokenStream>,
+    is_range: bool,
+}
+
+#[derive(Default)]
+struct StatusEnum {
+    variants: TokenStream,
+    codes: TokenStream,
+    ranges: TokenStream,
+    fallback: TokenStream,
+    uses_body: bool,
+}
+
+impl StatusEnum {
+    fn push(
+        &mut self,
+        ctx: &Context,
+        variant: Variant<'_>,
+        response: &ReferenceOr<Response>,
+    ) -> Result<(), String> {
+
+        let (definition, decode, uses_body) =
+            variant_and_decode(ctx, variant.ident, variant.type_name, response)?;
+
+        self.variants.extend(definition);
+
+        self.uses_body |= uses_body;
+
+        match variant.pattern {
+            Some(pattern) if variant.is_range => {
+                self.ranges.extend(quote! { #pattern => #decode, })
+            }
+            Some(pattern) => self.codes.extend(quote! { #pattern => #decode, }),
+            None => self.fallback = quote! { _ => #decode, },
+        }
+
+        Ok(())
+    }
+
+    fn generate(self, enum_ident: &Ident) -> TokenStream {
+
+        let Self {
+            variants,
+            codes,
+            ranges,
+            fallback,
+            uses_body,
+        } = self;
+
+        let from_response = if variants.is_empty() {
+
+            quote! {
+                fn from_response(_: StatusCode, _: &[u8]) -> Option<serde_json::Result<Self>> {
+                    None
+                }
+            }
+        } else {
+
+            let fallback = if fallback.is_empty() {
+
+                quote! { _ => None, }
+            } else {
+
+                fallback
+            };
+
+            let body = if uses_body {
+
+                quote! { body }
+            } else {
+
+                quote! { _ }
+            };
+
+            quote! {
+                fn from_response(status: StatusCode, #body: &[u8]) -> Option<serde_json::Result<Self>> {
+                    match status.as_u16() {
+                        #codes
+                        #ranges
+                        #fallback
+                    }
+                }
+            }
+        };
+
+        quote! {
+            #[derive(Debug, Clone)]
+            pub enum #enum_ident {
+                #variants
+            }
+
+            impl FromResponse for #enum_ident {
+                #from_response
+            }
+        }
+    }
+}
+
+type ResponseVariant = TokenStream;
+
+type ResponseDecode = TokenStream;
+
+type UsesBody = bool;
+
+fn variant_and_decode(
+    ctx: &Context,
+    variant_ident: &Ident,
+    type_name: &str,
+    response: &ReferenceOr<Response>,
+) -> Result<(ResponseVariant, ResponseDecode, UsesBody), String> {
+
+    let response = ctx.response(response)?;
+
+    let doc_comment = DocsGenerator::generate(Some(&response.description));
+
+    let schema = response
+        .content
+        .get("application/json")
+        .or_else(|| response.content.get("application/xml"))
+        .and_then(|mt| mt.schema.as_ref());
+
+    if let Some(schema) = schema {
+
+        let rust_type = schema_ref_type(ctx, type_name, schema)?;
+
+        return Ok((
+            quote! {
+                #doc_comment
+                #variant_ident(#rust_type),
+            },
+            quote! { Some(serde_json::from_slice(body).map(Self::#variant_ident)) },
+            true,
+        ));
+    }
+
+    if !response.content.is_empty() {
+
+        return Ok((
+            quote! {
+                #doc_comment
+                #variant_ident(Vec<u8>),
+            },
+            quote! { Some(Ok(Self::#variant_ident(body.to_vec()))) },
+            true,
+        ));
+    }
+
+    Ok((
+        quote! {
+            #doc_comment
+            #variant_ident,
+        },
+        quote! { Some(Ok(Self::#variant_ident)) },
+        false,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+
+    #[test]
+
+    fn test_responses_generator() {
+
+        let responses: openapiv3::Responses = serde_json::from_str(
+            r##"{
+                "200": {
+                    "description": "ok",
+                    "content": { "application/json": { "schema": { "$ref": "#/components/schemas/User" } } }
+                },
+                "204": { "description": "no content" },
+                "2XX": { "description": "other", "content": { "image/png": {} } },
+                "404": {
+                    "description": "not found",
+                    "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Problem" } } }
+                },
+                "default": { "description": "unexpected" }
+            }"##,
+        )
+        .unwrap();
+
+        let openapi: openapiv3::OpenAPI = serde_json::from_str(
+            r#"{
+                "openapi": "3.0.3",
+                "info": { "title": "t", "version": "1" },
+                "paths": {},
+                "components": { "schemas": { "User": { "type": "object" }, "Problem": { "type": "object" } } }
+            }"#,
+        )
+        .unwrap();
+
+        let ctx = crate::Context::new().with_spec(&openapi);
+
+        let output = super::ResponsesGenerator::new(&ctx, "GetUser", &responses)
+            .generate()
+            .unwrap()
+            .to_string();
+
+        assert!(output.contains("Status200 (User)"));
+
+        assert!(output.contains("Status204 ,"));
+
+        assert!(output.contains("Status2XX (Vec < u8 >)"));
+
+        assert!(output.contains("200 ..= 299 =>"));
+
+        assert!(output.contains("pub enum GetUserError { # [doc = \"not found\"] Status404 (Problem) , # [doc = \"unexpected\"] Default , }"));
+
+        assert!(output.contains("_ => Some (Ok (Self :: Default))"));
+    }
+
+    #[test]
+
+    fn test_error_statuses() {
+
+        let responses: openapiv3::Responses = serde_json::from_str(
+            r#"{
+                "201": { "description": "created" },
+                "400": { "description": "bad request", "content": { "application/json": { "schema": { "type": "string" } } } },
+                "5XX": { "description": "server error" },
+                "default": { "description": "unexpected", "content": { "application/json": { "schema": { "type": "boolean" } } } }
+            }"#,
+        )
+        .unwrap();
+
+        let ctx = crate::Context::new();
+
+        let output = super::ResponsesGenerator::new(&ctx, "AddPet", &responses)
+            .generate()
+            .unwrap()
+            .to_string();
+
+        assert!(output.contains("pub enum AddPetResponse { # [doc = \"created\"] Status201 , }"));
+
+        assert!(output.contains("pub enum AddPetError { # [doc = \"bad request\"] Status400 (String) , # [doc = \"server error\"] Status5XX , # [doc = \"unexpected\"] Default (bool) , }"));
+    }
+
+    #[test]
+
+    fn test_default_success() {
+
+        let responses: openapiv3::Responses = serde_json::from_str(
+            r#"{
+                "404": { "description": "not found" },
+                "default": { "description": "pet", "content": { "application/json": { "schema": { "type": "string" } } } }
+            }"#,
+        )
+        .unwrap();
+
+        let ctx = crate::Context::new();
+
+        let output = super::ResponsesGenerator::new(&ctx, "GetPet", &responses)
+            .generate()
+            .unwrap()
+            .to_string();
+
+        assert!(
+            output.contains("pub enum GetPetResponse { # [doc = \"pet\"] Default (String) , }")
+        );
+
+        assert!(output.contains("pub enum GetPetError { # [doc = \"not found\"] Status404 , }"));
+    }
+}
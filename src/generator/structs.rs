@@ -1,25 +1,44 @@
 use heck::ToPascalCase;
-use openapiv3::{SchemaKind, Type};
+use openapiv3::{ReferenceOr, SchemaKind, Type};
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 
 use crate::generator::{
-    docs::DocsGenerator, enums::EnumsGenerator, fields::FieldsGenerator, types::TypesGenerator,
+    all_of::{AllOfGenerator, object_type},
+    context::Context,
+    docs::DocsGenerator,
+    enums::EnumsGenerator,
+    fields::FieldsGenerator,
+    mapping::RUST_TYPE_EXTENSION,
+    one_of::OneOfGenerator,
+    types::{TypesGenerator, is_map},
 };
 
 pub struct StructsGenerator<'a> {
+    ctx: &'a Context,
     name: &'a str,
     schema: &'a openapiv3::Schema,
 }
 
 impl<'a> StructsGenerator<'a> {
-    pub fn new(name: &'a str, schema: &'a openapiv3::Schema) -> Self {
+    pub fn new(ctx: &'a Context, name: &'a str, schema: &'a openapiv3::Schema) -> Self {
 
-        Self { name, schema }
+        Self { ctx, name, schema }
     }
 
     pub fn generate(&self) -> Result<TokenStream, String> {
 
+        // mapped to a user supplied type, nothing to emit
+        if self
+            .schema
+            .schema_data
+            .extensions
+            .contains_key(RUST_TYPE_EXTENSION)
+        {
+
+            return Ok(quote! {});
+        }
+
         let struct_name = self.name.to_pascal_case();
 
         let struct_ident = format_ident!("{}", struct_name);
@@ -27,9 +46,26 @@ impl<'a> StructsGenerator<'a> {
         let doc_comment = DocsGenerator::generate(self.schema.schema_data.description.as_deref());
 
         match &self.schema.schema_kind {
-            SchemaKind::Type(Type::Object(obj)) => {
+            SchemaKind::Type(Type::Object(_)) | SchemaKind::Any(_)
+                if let Some(obj) = object_type(self.schema)
+                    && !is_map(&obj) =>
+            {
+
+                let fields = FieldsGenerator::new(self.ctx, &struct_name, &obj).generate()?;
+
+                Ok(quote! {
+                    #doc_comment
+                    #[derive(Debug, Clone, Serialize, Deserialize)]
+                    pub struct #struct_ident {
+                        #fields
+                    }
+                })
+            }
+            SchemaKind::AllOf { all_of }
+                if !matches!(all_of.as_slice(), [ReferenceOr::Reference { .. }]) =>
+            {
 
-                let fields = FieldsGenerator::new(&struct_name, obj).generate()?;
+                let fields = AllOfGenerator::new(self.ctx, &struct_name, all_of).generate()?;
 
                 Ok(quote! {
                     #doc_comment
@@ -39,6 +75,17 @@ impl<'a> StructsGenerator<'a> {
                     }
                 })
             }
+            SchemaKind::OneOf { one_of: parts } | SchemaKind::AnyOf { any_of: parts }
+                if parts.len() > 1 =>
+            {
+                OneOfGenerator::new(
+                    self.ctx,
+                    &struct_name,
+                    parts,
+                    self.schema.schema_data.discriminator.as_ref(),
+                )
+                .generate(doc_comment)
+            }
             SchemaKind::Type(Type::String(schema)) if !schema.enumeration.is_empty() => {
 
                 let variants = EnumsGenerator::new(schema).generate()?;
@@ -53,7 +100,8 @@ impl<'a> StructsGenerator<'a> {
             }
             _ => {
 
-                let rust_type = TypesGenerator::new(self.schema).generate()?;
+                let rust_type =
+                    TypesGenerator::new(self.ctx, &struct_name, self.schema).generate()?;
 
                 Ok(quote! {
                     #doc_comment
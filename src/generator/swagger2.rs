@@ -0,0 +1,779 @@
+use serde_json::{Map, Value, json};
+
+use crate::{DATA_KEYS, NAME_MAPS};
+
+/// Parameter keywords that move into the `schema` of an OpenAPI 3 parameter.
+const SCHEMA_KEYWORDS: [&str; 17] = [
+    "type",
+    "format",
+    "items",
+    "default",
+    "maximum",
+    "exclusiveMaximum",
+    "minimum",
+    "exclusiveMinimum",
+    "maxLength",
+    "minLength",
+    "pattern",
+    "maxItems",
+    "minItems",
+    "uniqueItems",
+    "enum",
+    "multipleOf",
+    "x-nullable",
+];
+
+const METHODS: [&str; 7] = ["get", "put", "post", "delete", "options", "head", "patch"];
+
+const DEFAULT_MEDIA_TYPE: &str = "application/json";
+
+/// Whether `spec` is a Swagger 2.0 document.
+pub fn is_swagger_2(spec: &Value) -> bool {
+
+    spec.get("swagger").and_then(Value::as_str) == Some("2.0")
+}
+
+/// Converts a Swagger 2.0 document into an OpenAPI 3.0 one:
+///
+/// - `definitions`, `parameters`, `responses` and `securityDefinitions` move
+///   into `components`, with their references rewritten
+/// - `in: body` and `in: formData` parameters become a `requestBody`, typed
+///   after `consumes`
+/// - response schemas become `content`, typed after `produces`
+/// - `host`, `basePath` and `schemes` become `servers`
+pub fn convert_swagger_2(spec: Value) -> Value {
+
+    let Value::Object(mut swagger) = spec else {
+
+        return spec;
+    };
+
+    let consumes = media_types(swagger.get("consumes"));
+
+    let produces = media_types(swagger.get("produces"));
+
+    let mut components = Map::new();
+
+    let mut request_bodies = Map::new();
+
+    let mut parameters = Map::new();
+
+    if let Some(Value::Object(global)) = swagger.remove("parameters") {
+
+        for (name, parameter) in global {
+
+            match parameter.get("in").and_then(Value::as_str) {
+                Some("body") => {
+
+                    request_bodies.insert(name, body_request(&parameter, &consumes));
+                }
+                Some("formData") => {
+
+                    request_bodies.insert(name, form_request(&[&parameter], &consumes));
+                }
+                _ => {
+
+                    parameters.insert(name, convert_parameter(parameter));
+                }
+            }
+        }
+    }
+
+    if let Some(definitions) = swagger.remove("definitions") {
+
+        components.insert("schemas".to_string(), definitions);
+    }
+
+    if !parameters.is_empty() {
+
+        components.insert("parameters".to_string(), Value::Object(parameters));
+    }
+
+    if !request_bodies.is_empty() {
+
+        components.insert(
+            "requestBodies".to_string(),
+            Value::Object(request_bodies.clone()),
+        );
+    }
+
+    if let Some(Value::Object(responses)) = swagger.remove("responses") {
+
+        let responses = responses
+            .into_iter()
+            .map(|(name, response)| (name, convert_response(response, &produces)))
+            .collect();
+
+        components.insert("responses".to_string(), Value::Object(responses));
+    }
+
+    if let Some(Value::Object(definitions)) = swagger.remove("securityDefinitions") {
+
+        let schemes = definitions
+            .into_iter()
+            .map(|(name, scheme)| (name, convert_security_scheme(scheme)))
+            .collect();
+
+        components.insert("securitySchemes".to_string(), Value::Object(schemes));
+    }
+
+    let mut paths = Map::new();
+
+    if let Some(Value::Object(items)) = swagger.remove("paths") {
+
+        for (path, item) in items {
+
+            paths.insert(
+                path,
+                convert_path_item(item, &consumes, &produces, &request_bodies),
+            );
+        }
+    }
+
+    let mut openapi = Map::new();
+
+    openapi.insert("openapi".to_string(), "3.0.3".into());
+
+    for key in ["info", "tags", "security", "externalDocs"] {
+
+        if let Some(value) = swagger.remove(key) {
+
+            openapi.insert(key.to_string(), value);
+        }
+    }
+
+    if let Some(servers) = servers(&swagger) {
+
+        openapi.insert("servers".to_string(), servers);
+    }
+
+    openapi.insert("paths".to_string(), Value::Object(paths));
+
+    openapi.insert("components".to_string(), Value::Object(components));
+
+    for (key, value) in swagger {
+
+        if key.starts_with("x-") {
+
+            openapi.insert(key, value);
+        }
+    }
+
+    let mut openapi = Value::Object(openapi);
+
+    convert_schemas(&mut openapi, false);
+
+    openapi
+}
+
+fn media_types(value: Option<&Value>) -> Vec<String> {
+
+    let media_types: Vec<String> = value
+        .and_then(Value::as_array)
+        .map(|types| {
+
+            types
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if media_types.is_empty() {
+
+        vec![DEFAULT_MEDIA_TYPE.to_string()]
+    } else {
+
+        media_types
+    }
+}
+
+fn servers(swagger: &Map<String, Value>) -> Option<Value> {
+
+    let host = swagger.get("host").and_then(Value::as_str)?;
+
+    let base_path = swagger
+        .get("basePath")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+
+    let schemes: Vec<&str> = swagger
+        .get("schemes")
+        .and_then(Value::as_array)
+        .map(|schemes| schemes.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_else(|| vec!["https"]);
+
+    let servers = schemes
+        .into_iter()
+        .map(|scheme| json!({ "url": format!("{scheme}://{host}{base_path}") }))
+        .collect();
+
+    Some(Value::Array(servers))
+}
+
+fn convert_path_item(
+    item: Value,
+    consumes: &[String],
+    produces: &[String],
+    request_bodies: &Map<String, Value>,
+) -> Value {
+
+    let Value::Object(mut item) = item else {
+
+        return item;
+    };
+
+    // body and form parameters shared by the path apply to each operation
+    let shared = item
+        .remove("parameters")
+        .and_then(|parameters| parameters.as_array().cloned())
+        .unwrap_or_default();
+
+    let (shared_body, shared) = split_body_parameters(shared, request_bodies);
+
+    if !shared.is_empty() {
+
+        item.insert("parameters".to_string(), Value::Array(shared));
+    }
+
+    for method in METHODS {
+
+        if let Some(Value::Object(operation)) = item.get_mut(method) {
+
+            convert_operation(operation, &shared_body, consumes, produces, request_bodies);
+        }
+    }
+
+    Value::Object(item)
+}
+
+/// Splits body and form parameters, possibly referenced, from the others,
+/// which are converted.
+fn split_body_parameters(
+    parameters: Vec<Value>,
+    request_bodies: &Map<String, Value>,
+) -> (Vec<Value>, Vec<Value>) {
+
+    let mut body = Vec::new();
+
+    let mut others = Vec::new();
+
+    for parameter in parameters {
+
+        let is_body = match parameter.get("$ref").and_then(Value::as_str) {
+            Some(reference) => reference
+                .strip_prefix("#/parameters/")
+                .is_some_and(|name| request_bodies.contains_key(name)),
+            None => matches!(
+                parameter.get("in").and_then(Value::as_str),
+                Some("body" | "formData")
+            ),
+        };
+
+        if is_body {
+
+            body.push(parameter);
+        } else if parameter.get("$ref").is_some() {
+
+            others.push(parameter);
+        } else {
+
+            others.push(convert_parameter(parameter));
+        }
+    }
+
+    (body, others)
+}
+
+fn convert_operation(
+    operation: &mut Map<String, Value>,
+    shared_body: &[Value],
+    consumes: &[String],
+    produces: &[String],
+    request_bodies: &Map<String, Value>,
+) {
+
+    let consumes = match operation.remove("consumes") {
+        Some(value) => media_types(Some(&value)),
+        None => consumes.to_vec(),
+    };
+
+    let produces = match operation.remove("produces") {
+        Some(value) => media_types(Some(&value)),
+        None => produces.to_vec(),
+    };
+
+    let parameters = operation
+        .remove("parameters")
+        .and_then(|parameters| parameters.as_array().cloned())
+        .unwrap_or_default();
+
+    let (mut body, parameters) = split_body_parameters(parameters, request_bodies);
+
+    if body.is_empty() {
+
+        body = shared_body.to_vec();
+    }
+
+    if !parameters.is_empty() {
+
+        operation.insert("parameters".to_string(), Value::Array(parameters));
+    }
+
+    let form: Vec<&Value> = body
+        .iter()
+        .filter(|parameter| parameter.get("in").and_then(Value::as_str) == Some("formData"))
+        .collect();
+
+    let request_body = if !form.is_empty() {
+
+        Some(form_request(&form, &consumes))
+    } else if let Some(parameter) = body.first() {
+
+        match parameter.get("$ref").and_then(Value::as_str) {
+            Some(reference) => Some(json!({
+                "$ref": reference.replace("#/parameters/", "#/components/requestBodies/")
+            })),
+            None => Some(body_request(parameter, &consumes)),
+        }
+    } else {
+
+        None
+    };
+
+    if let Some(request_body) = request_body {
+
+        operation.insert("requestBody".to_string(), request_body);
+    }
+
+    if let Some(Value::Object(responses)) = operation.remove("responses") {
+
+        let responses = responses
+            .into_iter()
+            .map(|(status, response)| (status, convert_response(response, &produces)))
+            .collect();
+
+        operation.insert("responses".to_string(), Value::Object(responses));
+    }
+}
+
+fn body_request(parameter: &Value, consumes: &[String]) -> Value {
+
+    let schema = parameter.get("schema").cloned().unwrap_or(json!({}));
+
+    let content: Map<String, Value> = consumes
+        .iter()
+        .map(|media_type| (media_type.clone(), json!({ "schema": schema })))
+        .collect();
+
+    let mut request_body = json!({
+        "content": content,
+        "required": parameter.get("required").cloned().unwrap_or(false.into()),
+    });
+
+    if let Some(description) = parameter.get("description") {
+
+        request_body["description"] = description.clone();
+    }
+
+    request_body
+}
+
+/// Collects `formData` parameters into a single object schema, sent as
+/// `multipart/form-data` when the operation consumes it or uploads files.
+fn form_request(parameters: &[&Value], consumes: &[String]) -> Value {
+
+    let mut properties = Map::new();
+
+    let mut required = Vec::new();
+
+    for parameter in parameters {
+
+        let Some(name) = parameter.get("name").and_then(Value::as_str) else {
+
+            continue;
+        };
+
+        if parameter.get("required").and_then(Value::as_bool) == Some(true) {
+
+            required.push(Value::from(name));
+        }
+
+        properties.insert(name.to_string(), parameter_schema(parameter));
+    }
+
+    let uploads_files = parameters
+        .iter()
+        .any(|parameter| parameter.get("type").and_then(Value::as_str) == Some("file"));
+
+    let media_type = if uploads_files || consumes.iter().any(|c| c == "multipart/form-data") {
+
+        "multipart/form-data"
+    } else {
+
+        "application/x-www-form-urlencoded"
+    };
+
+    let mut schema = json!({ "type": "object", "properties": properties });
+
+    let has_required = !required.is_empty();
+
+    if has_required {
+
+        schema["required"] = Value::Array(required);
+    }
+
+    json!({
+        "content": { media_type: { "schema": schema } },
+        "required": has_required,
+    })
+}
+
+fn parameter_schema(parameter: &Value) -> Value {
+
+    let mut schema = Map::new();
+
+    for keyword in SCHEMA_KEYWORDS {
+
+        if let Some(value) = parameter.get(keyword) {
+
+            schema.insert(keyword.to_string(), value.clone());
+        }
+    }
+
+    Value::Object(schema)
+}
+
+fn convert_parameter(parameter: Value) -> Value {
+
+    let schema = parameter_schema(&parameter);
+
+    let Value::Object(mut parameter) = parameter else {
+
+        return parameter;
+    };
+
+    for keyword in SCHEMA_KEYWORDS {
+
+        parameter.remove(keyword);
+    }
+
+    match parameter
+        .remove("collectionFormat")
+        .as_ref()
+        .and_then(Value::as_str)
+    {
+        Some("multi") => {
+
+            parameter.insert("explode".to_string(), true.into());
+        }
+        Some("ssv") => {
+
+            parameter.insert("style".to_string(), "spaceDelimited".into());
+        }
+        Some("pipes") => {
+
+            parameter.insert("style".to_string(), "pipeDelimited".into());
+        }
+        Some(_) => {
+
+            parameter.insert("explode".to_string(), false.into());
+        }
+        None => {}
+    }
+
+    parameter.remove("allowEmptyValue");
+
+    parameter.insert("schema".to_string(), schema);
+
+    Value::Object(parameter)
+}
+
+fn convert_response(response: Value, produces: &[String]) -> Value {
+
+    let Value::Object(mut response) = response else {
+
+        return response;
+    };
+
+    if response.contains_key("$ref") {
+
+        return Value::Object(response);
+    }
+
+    if let Some(schema) = response.remove("schema") {
+
+        let content: Map<String, Value> = produces
+            .iter()
+            .map(|media_type| (media_type.clone(), json!({ "schema": schema })))
+            .collect();
+
+        response.insert("content".to_string(), Value::Object(content));
+    }
+
+    if let Some(Value::Object(headers)) = response.remove("headers") {
+
+        let headers = headers
+            .into_iter()
+            .map(|(name, header)| {
+
+                let description = header.get("description").cloned();
+
+                let mut header = json!({ "schema": parameter_schema(&header) });
+
+                if let Some(description) = description {
+
+                    header["description"] = description;
+                }
+
+                (name, header)
+            })
+            .collect();
+
+        response.insert("headers".to_string(), Value::Object(headers));
+    }
+
+    response.entry("description").or_insert_with(|| "".into());
+
+    Value::Object(response)
+}
+
+fn convert_security_scheme(scheme: Value) -> Value {
+
+    match scheme.get("type").and_then(Value::as_str) {
+        Some("basic") => {
+
+            let mut converted = json!({ "type": "http", "scheme": "basic" });
+
+            if let Some(description) = scheme.get("description") {
+
+                converted["description"] = description.clone();
+            }
+
+            converted
+        }
+        Some("oauth2") => {
+
+            let flow = match scheme.get("flow").and_then(Value::as_str) {
+                Some("accessCode") => "authorizationCode",
+                Some("application") => "clientCredentials",
+                Some(flow) => flow,
+                None => "implicit",
+            };
+
+            let mut converted_flow = Map::new();
+
+            for key in ["authorizationUrl", "tokenUrl"] {
+
+                if let Some(url) = scheme.get(key) {
+
+                    converted_flow.insert(key.to_string(), url.clone());
+                }
+            }
+
+            converted_flow.insert(
+                "scopes".to_string(),
+                scheme.get("scopes").cloned().unwrap_or(json!({})),
+            );
+
+            let mut converted = json!({ "type": "oauth2", "flows": { flow: converted_flow } });
+
+            if let Some(description) = scheme.get("description") {
+
+                converted["description"] = description.clone();
+            }
+
+            converted
+        }
+        _ => scheme,
+    }
+}
+
+/// Rewrites references and the Swagger only schema keywords across the whole
+/// document. `name_map` is set for the values of [`NAME_MAPS`], whose keys
+/// are names rather than keywords.
+fn convert_schemas(value: &mut Value, name_map: bool) {
+
+    match value {
+        Value::Array(items) => {
+            for item in items {
+
+                convert_schemas(item, false);
+            }
+        }
+        Value::Object(object) if name_map => {
+            for value in object.values_mut() {
+
+                convert_schemas(value, false);
+            }
+        }
+        Value::Object(object) => {
+
+            if let Some(Value::String(reference)) = object.get_mut("$ref") {
+
+                *reference = convert_reference(reference);
+            }
+
+            if let Some(Value::Bool(nullable)) = object.remove("x-nullable") {
+
+                object.insert("nullable".to_string(), nullable.into());
+            }
+
+            if object.get("type").and_then(Value::as_str) == Some("file") {
+
+                object.insert("type".to_string(), "string".into());
+
+                object.insert("format".to_string(), "binary".into());
+            }
+
+            if let Some(Value::String(property)) = object.get("discriminator") {
+
+                let discriminator = json!({ "propertyName": property });
+
+                object.insert("discriminator".to_string(), discriminator);
+            }
+
+            for (key, value) in object.iter_mut() {
+
+                if !DATA_KEYS.contains(&key.as_str()) {
+
+                    convert_schemas(value, NAME_MAPS.contains(&key.as_str()));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn convert_reference(reference: &str) -> String {
+
+    for (from, to) in [
+        ("#/definitions/", "#/components/schemas/"),
+        ("#/parameters/", "#/components/parameters/"),
+        ("#/responses/", "#/components/responses/"),
+    ] {
+
+        if let Some(name) = reference.strip_prefix(from) {
+
+            return format!("{to}{name}");
+        }
+    }
+
+    reference.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use serde_json::json;
+
+    #[test]
+
+    fn test_convert_swagger_2() {
+
+        let spec = json!({
+            "swagger": "2.0",
+            "info": { "title": "t", "version": "1" },
+            "host": "api.example.com",
+            "basePath": "/v1",
+            "produces": ["application/json"],
+            "definitions": { "Pet": { "type": "object", "x-nullable": true } },
+            "paths": {
+                "/pets": {
+                    "post": {
+                        "operationId": "addPet",
+                        "parameters": [
+                            { "name": "pet", "in": "body", "required": true, "schema": { "$ref": "#/definitions/Pet" } },
+                            { "name": "tags", "in": "query", "type": "array", "items": { "type": "string" }, "collectionFormat": "multi" }
+                        ],
+                        "responses": { "200": { "description": "ok", "schema": { "$ref": "#/definitions/Pet" } } }
+                    }
+                }
+            }
+        });
+
+        assert!(super::is_swagger_2(&spec));
+
+        assert_eq!(
+            super::convert_swagger_2(spec),
+            json!({
+                "openapi": "3.0.3",
+                "info": { "title": "t", "version": "1" },
+                "servers": [{ "url": "https://api.example.com/v1" }],
+                "components": { "schemas": { "Pet": { "type": "object", "nullable": true } } },
+                "paths": {
+                    "/pets": {
+                        "post": {
+                            "operationId": "addPet",
+                            "parameters": [
+                                { "name": "tags", "in": "query", "explode": true, "schema": { "type": "array", "items": { "type": "string" } } }
+                            ],
+                            "requestBody": {
+                                "required": true,
+                                "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Pet" } } }
+                            },
+                            "responses": {
+                                "200": {
+                                    "description": "ok",
+                                    "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Pet" } } }
+                                }
+                            }
+                        }
+                    }
+                }
+            })
+        );
+    }
+
+    #[test]
+
+    fn test_convert_properties_named_like_data_keys() {
+
+        let spec = json!({
+            "swagger": "2.0",
+            "info": { "title": "t", "version": "1" },
+            "definitions": {
+                "Owner": { "type": "object" },
+                "Pet": {
+                    "type": "object",
+                    "properties": {
+                        "default": { "$ref": "#/definitions/Owner" },
+                        "example": { "type": "string", "x-nullable": true }
+                    },
+                    "example": { "$ref": "#/definitions/Owner" }
+                }
+            },
+            "paths": {
+                "/pets": {
+                    "get": {
+                        "responses": { "default": { "description": "error", "schema": { "$ref": "#/definitions/Owner" } } }
+                    }
+                }
+            }
+        });
+
+        let converted = super::convert_swagger_2(spec);
+
+        let pet = &converted["components"]["schemas"]["Pet"];
+
+        assert_eq!(
+            pet["properties"]["default"],
+            json!({ "$ref": "#/components/schemas/Owner" })
+        );
+
+        assert_eq!(
+            pet["properties"]["example"],
+            json!({ "type": "string", "nullable": true })
+        );
+
+        // instance data is kept as is
+        assert_eq!(pet["example"], json!({ "$ref": "#/definitions/Owner" }));
+
+        assert_eq!(
+            converted["paths"]["/pets"]["get"]["responses"]["default"]["content"]["application/json"]
+                ["schema"],
+            json!({ "$ref": "#/components/schemas/Owner" })
+        );
+    }
+}
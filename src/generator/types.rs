@@ -1,22 +1,39 @@
-use heck::ToPascalCase;
-use openapiv3::{ReferenceOr, SchemaKind, Type};
+use openapiv3::{AdditionalProperties, ObjectType, ReferenceOr, SchemaKind, Type};
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 
+use crate::generator::{
+    context::Context,
+    formats::string_type,
+    mapping::{RUST_TYPE_EXTENSION, rust_path},
+    structs::StructsGenerator,
+};
+
 pub struct TypesGenerator<'a> {
+    ctx: &'a Context,
+    name: &'a str,
     schema: &'a openapiv3::Schema,
 }
 
 impl<'a> TypesGenerator<'a> {
-    pub fn new(schema: &'a openapiv3::Schema) -> Self {
+    /// `name` is given to the nested type emitted when `schema` is an inline
+    /// object or composition.
+    pub fn new(ctx: &'a Context, name: &'a str, schema: &'a openapiv3::Schema) -> Self {
 
-        Self { schema }
+        Self { ctx, name, schema }
     }
 
     pub fn generate(&self) -> Result<TokenStream, String> {
 
+        if let Some(path) = mapped_type(self.ctx, self.schema) {
+
+            return rust_path(path);
+        }
+
         match &self.schema.schema_kind {
-            SchemaKind::Type(Type::String(_)) => Ok(quote! { String }),
+            SchemaKind::Type(Type::String(string_schema)) => {
+                Ok(string_type(self.ctx, string_schema))
+            }
             SchemaKind::Type(Type::Integer(int_schema)) => {
                 if let openapiv3::VariantOrUnknownOrEmpty::Item(openapiv3::IntegerFormat::Int64) =
                     int_schema.format
@@ -48,19 +65,23 @@ impl<'a> TypesGenerator<'a> {
 
                     let item_type = match items {
                         ReferenceOr::Reference { reference } => {
+                            self.ctx.reference_type(reference)?
+                        }
+                        ReferenceOr::Item(schema) => {
+
+                            let item_name = format!("{}Item", self.name);
 
-                            if let Some(type_name) = reference.strip_prefix("#/components/schemas/")
-                            {
+                            let item_type =
+                                TypesGenerator::new(self.ctx, &item_name, schema).generate()?;
 
-                                let type_ident = format_ident!("{}", type_name.to_pascal_case());
+                            if schema.schema_data.nullable {
 
-                                quote! { #type_ident }
+                                quote! { Option<#item_type> }
                             } else {
 
-                                quote! { serde_json::Value }
+                                item_type
                             }
                         }
-                        ReferenceOr::Item(schema) => Self::new(schema).generate()?,
                     };
 
                     Ok(quote! { Vec<#item_type> })
@@ -69,8 +90,168 @@ impl<'a> TypesGenerator<'a> {
                     Ok(quote! { Vec<serde_json::Value> })
                 }
             }
-            SchemaKind::Type(Type::Object(_)) => Ok(quote! { HashMap<String, serde_json::Value> }),
+            SchemaKind::Type(Type::Object(obj)) if !obj.properties.is_empty() => self.inline_type(),
+            SchemaKind::Type(Type::Object(obj)) => {
+                map_type(self.ctx, self.name, obj.additional_properties.as_ref())
+            }
+            // `allOf` with a single part is the usual way to attach a
+            // description or `nullable` to a `$ref`
+            SchemaKind::AllOf { all_of: parts }
+            | SchemaKind::OneOf { one_of: parts }
+            | SchemaKind::AnyOf { any_of: parts }
+                if parts.len() == 1 =>
+            {
+                schema_ref_type(self.ctx, self.name, &parts[0])
+            }
+            SchemaKind::AllOf { .. } | SchemaKind::OneOf { .. } | SchemaKind::AnyOf { .. } => {
+                self.inline_type()
+            }
+            SchemaKind::Any(any) if !any.properties.is_empty() => self.inline_type(),
+            SchemaKind::Any(any) if any.typ.as_deref() == Some("object") => {
+                map_type(self.ctx, self.name, any.additional_properties.as_ref())
+            }
             _ => Ok(quote! { serde_json::Value }),
         }
     }
+
+    /// Emits the schema as a named type through the context and refers to it
+    /// by name.
+    fn inline_type(&self) -> Result<TokenStream, String> {
+
+        let name = self.ctx.inline_type_name(self.name);
+
+        let definition = StructsGenerator::new(self.ctx, &name, self.schema).generate()?;
+
+        self.ctx.push_inline_type(definition);
+
+        let type_ident = format_ident!("{}", name);
+
+        Ok(quote! { #type_ident })
+    }
+}
+
+/// The Rust path configured for a schema, through the `x-rust-type`
+/// extension or its `format`.
+pub fn mapped_type<'a>(ctx: &'a Context, schema: &'a openapiv3::Schema) -> Option<&'a str> {
+
+    if let Some(path) = schema
+        .schema_data
+        .extensions
+        .get(RUST_TYPE_EXTENSION)
+        .and_then(|path| path.as_str())
+    {
+
+        return Some(path);
+    }
+
+    let format = match &schema.schema_kind {
+        SchemaKind::Type(Type::String(s)) => serde_json::to_value(&s.format),
+        SchemaKind::Type(Type::Integer(i)) => serde_json::to_value(&i.format),
+        SchemaKind::Type(Type::Number(n)) => serde_json::to_value(&n.format),
+        SchemaKind::Any(any) => serde_json::to_value(&any.format),
+        _ => return None,
+    };
+
+    ctx.type_mapping().format(format.ok()?.as_str()?)
+}
+
+/// Whether an object is a plain map, with no fixed properties and an
+/// explicit `additionalProperties`.
+pub fn is_map(obj: &ObjectType) -> bool {
+
+    obj.properties.is_empty()
+        && matches!(
+            obj.additional_properties,
+            Some(AdditionalProperties::Schema(_)) | Some(AdditionalProperties::Any(true))
+        )
+}
+
+/// `HashMap` typed after `additionalProperties`, with untyped values when it
+/// is absent or `true`.
+pub fn map_type(
+    ctx: &Context,
+    name: &str,
+    additional_properties: Option<&AdditionalProperties>,
+) -> Result<TokenStream, String> {
+
+    let value_type = match additional_properties {
+        Some(AdditionalProperties::Schema(schema)) => {
+            schema_ref_type(ctx, &format!("{name}Value"), schema)?
+        }
+        _ => quote! { serde_json::Value },
+    };
+
+    Ok(quote! { HashMap<String, #value_type> })
+}
+
+/// Rust type of a schema that may be a `$ref` to a component schema.
+pub fn schema_ref_type(
+    ctx: &Context,
+    name: &str,
+    schema: &ReferenceOr<openapiv3::Schema>,
+) -> Result<TokenStream, String> {
+
+    match schema {
+        ReferenceOr::Reference { reference } => ctx.reference_type(reference),
+        ReferenceOr::Item(schema) => TypesGenerator::new(ctx, name, schema).generate(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    #[test]
+
+    fn test_inline_type_names() {
+
+        let generator = crate::OpenAPIGenerator::from_json(
+            r#"{
+                "openapi": "3.0.3",
+                "info": { "title": "t", "version": "1" },
+                "paths": {},
+                "components": {
+                    "schemas": {
+                        "Parent": {
+                            "type": "object",
+                            "properties": {
+                                "owner": {
+                                    "type": "object",
+                                    "properties": {
+                                        "address": {
+                                            "type": "object",
+                                            "properties": { "city": { "type": "string" } }
+                                        }
+                                    }
+                                },
+                                "tags": {
+                                    "type": "array",
+                                    "items": {
+                                        "type": "object",
+                                        "properties": { "label": { "type": "string" } }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let types = generator.gen_types().to_string();
+
+        assert!(types.contains(
+            "pub struct Parent { pub owner : Option < ParentOwner > , pub tags : Option < Vec < ParentTagsItem > > , }"
+        ));
+
+        assert!(
+            types.contains(
+                "pub struct ParentOwner { pub address : Option < ParentOwnerAddress > , }"
+            )
+        );
+
+        assert!(types.contains("pub struct ParentOwnerAddress { pub city : Option < String > , }"));
+
+        assert!(types.contains("pub struct ParentTagsItem { pub label : Option < String > , }"));
+    }
 }
@@ -1,8 +1,9 @@
-use std::{collections::HashMap, io::Write};
+use std::path::Path;
 
-use super::OperationMap;
+use super::{
+    Dependency, FileSystemOutput, OperationMap, Output, create_rust_safe_ident, format_tokens,
+};
 use proc_macro2::TokenStream;
-use quote::format_ident;
 
 const CARGO_TOML: &str = include_str!("../../template/Cargo.toml");
 
@@ -12,16 +13,21 @@ const LIB_RS: &str = include_str!("../../template/src/lib.rs");
 
 const REQUEST_RS: &str = include_str!("../../template/src/request.rs");
 
+const RESPONSE_RS: &str = include_str!("../../template/src/response.rs");
+
 const ROUTE_RS: &str = include_str!("../../template/src/route.rs");
 
 const CLIENT_RS: &str = include_str!("../../template/src/client.rs");
 
 const ERROR_RS: &str = include_str!("../../template/src/error.rs");
 
+const FORMATS_RS: &str = include_str!("../../template/src/formats.rs");
+
 pub struct CrateWriter<'a> {
     pub relative_path: &'a str,
     pub types: TokenStream,
-    pub methods: HashMap<String, TokenStream>,
+    pub methods: OperationMap,
+    pub dependencies: Vec<Dependency>,
 }
 
 impl<'a> CrateWriter<'a> {
@@ -31,78 +37,94 @@ impl<'a> CrateWriter<'a> {
             relative_path,
             types,
             methods,
+            dependencies: Vec::new(),
         }
     }
 
+    /// Extra crates to add to the generated `Cargo.toml`, see
+    /// `OpenAPIGenerator::dependencies`.
+    pub fn with_dependencies(mut self, dependencies: Vec<Dependency>) -> Self {
+
+        self.dependencies = dependencies;
+
+        self
+    }
+
     pub fn add_method(&mut self, name: String, token: TokenStream) {
 
         self.methods.insert(name, token);
     }
 
+    /// Writes the crate into `relative_path`, see [`CrateWriter::write_to`].
     pub fn write(&self) -> std::io::Result<()> {
 
-        let path = std::path::Path::new(self.relative_path);
+        self.write_to(&mut FileSystemOutput::new(self.relative_path))
+    }
 
-        std::fs::create_dir_all(path)?;
+    /// Writes the crate. Generated files are rewritten on every run, while the
+    /// scaffolding meant to be edited (`Cargo.toml`, `rustfmt.toml`,
+    /// `lib.rs` and `client.rs`) is only created when missing.
+    pub fn write_to(&self, output: &mut impl Output) -> std::io::Result<()> {
 
-        let methods_mod_path = std::path::Path::new(path).join("src/methods");
+        self.write_cargo_toml(output)?;
 
-        if !methods_mod_path.exists() {
+        write_scaffolding(output, "rustfmt.toml", RUSTFMT_TOML)?;
 
-            std::fs::create_dir_all(&methods_mod_path)?;
-        }
+        self.write_lib_rs(output)?;
+
+        write_scaffolding(output, "src/client.rs", CLIENT_RS)?;
 
-        let src_path = std::path::Path::new(path).join("src");
+        output.write(Path::new("src/error.rs"), ERROR_RS.to_string())?;
 
-        if !src_path.exists() {
+        if self.dependencies.contains(&Dependency::Base64) {
 
-            std::fs::create_dir_all(&src_path)?;
+            output.write(Path::new("src/formats.rs"), FORMATS_RS.to_string())?;
         }
 
-        std::fs::write(std::path::Path::new(path).join("Cargo.toml"), CARGO_TOML)?;
+        output.write(Path::new("src/request.rs"), REQUEST_RS.to_string())?;
 
-        std::fs::write(
-            std::path::Path::new(path).join("rustfmt.toml"),
-            RUSTFMT_TOML,
-        )?;
+        output.write(Path::new("src/response.rs"), RESPONSE_RS.to_string())?;
 
-        std::fs::write(std::path::Path::new(path).join("src/lib.rs"), LIB_RS)?;
+        output.write(Path::new("src/route.rs"), ROUTE_RS.to_string())?;
 
-        std::fs::write(
-            std::path::Path::new(path).join("src/request.rs"),
-            REQUEST_RS,
-        )?;
+        // relative imports only, so the crate also works as a module, see
+        // `GeneratedCrate::module`
+        let formats_import = if self.dependencies.contains(&Dependency::Base64) {
 
-        std::fs::write(std::path::Path::new(path).join("src/route.rs"), ROUTE_RS)?;
+            quote::quote! { use super::Base64; }
+        } else {
 
-        std::fs::write(std::path::Path::new(path).join("src/client.rs"), CLIENT_RS)?;
+            TokenStream::new()
+        };
 
-        std::fs::write(std::path::Path::new(path).join("src/error.rs"), ERROR_RS)?;
+        let types = &self.types;
 
-        std::fs::write(
-            std::path::Path::new(path).join("src/types.rs"),
-            self.types.to_string(),
+        output.write(
+            Path::new("src/types.rs"),
+            format_tokens(&quote::quote! {
+                #formats_import
+                #types
+            }),
         )?;
 
-        let mut methods_mod_file = std::fs::File::create(methods_mod_path.join("mod.rs"))?;
+        let methods_mod_path = Path::new("src/methods");
 
         let mut methods_mod_token = TokenStream::new();
 
         methods_mod_token.extend(quote::quote! {
             use super::types::*;
+            use super::{FromResponse, Request};
+            #formats_import
         });
 
         for (name, token) in &self.methods {
 
-            let file_name = format!("{name}.rs");
-
-            let file_path = methods_mod_path.join(&file_name);
-
-            let mut file = std::fs::File::create(file_path)?;
+            output.write(
+                &methods_mod_path.join(format!("{name}.rs")),
+                format_tokens(token),
+            )?;
 
-            file.write_all(token.to_string().as_bytes())?;
-
-            let name_ident = format_ident!("{}", name);
+            let name_ident = create_rust_safe_ident(name);
 
             methods_mod_token.extend(quote::quote! {
                 pub mod #name_ident;
@@ -110,8 +132,194 @@ impl<'a> CrateWriter<'a> {
             });
         }
 
-        methods_mod_file.write_all(methods_mod_token.to_string().as_bytes())?;
+        output.write(
+            &methods_mod_path.join("mod.rs"),
+            format_tokens(&methods_mod_token),
+        )?;
+
+        self.remove_stale_methods(output, methods_mod_path)?;
+
+        Ok(())
+    }
+
+    /// Creates the manifest, or adds the dependencies it lacks to the
+    /// `[dependencies]` table of an existing one.
+    fn write_cargo_toml(&self, output: &mut impl Output) -> std::io::Result<()> {
+
+        let file_path = Path::new("Cargo.toml");
+
+        let Some(manifest) = output.read(file_path)? else {
+
+            return output.write(file_path, self.cargo_toml());
+        };
+
+        let missing: Vec<&str> = self
+            .dependencies
+            .iter()
+            .map(|dependency| dependency.manifest_entry())
+            .filter(|entry| {
+
+                let name = entry.split('=').next().unwrap_or_default().trim();
+
+                !manifest
+                    .lines()
+                    .any(|line| line.split('=').next().unwrap_or_default().trim() == name)
+            })
+            .collect();
+
+        if missing.is_empty() {
+
+            return Ok(());
+        }
+
+        let mut merged = String::new();
+
+        let mut inserted = false;
+
+        for line in manifest.lines() {
+
+            merged.push_str(line);
+
+            merged.push('\n');
+
+            if !inserted && line.trim() == "[dependencies]" {
+
+                for entry in &missing {
+
+                    merged.push_str(entry);
+
+                    merged.push('\n');
+                }
+
+                inserted = true;
+            }
+        }
+
+        if !inserted {
+
+            merged.push_str("\n[dependencies]\n");
+
+            for entry in &missing {
+
+                merged.push_str(entry);
+
+                merged.push('\n');
+            }
+        }
+
+        output.write(file_path, merged)
+    }
+
+    /// Creates `lib.rs`, or adds the module declarations and re-exports it
+    /// lacks to an existing one.
+    fn write_lib_rs(&self, output: &mut impl Output) -> std::io::Result<()> {
+
+        let file_path = Path::new("src/lib.rs");
+
+        let Some(lib) = output.read(file_path)? else {
+
+            return output.write(file_path, self.lib_rs());
+        };
+
+        let required = self.lib_rs();
+
+        let missing: Vec<&str> = required
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !lib.lines().any(|kept| declares(kept, line)))
+            .collect();
+
+        if missing.is_empty() {
+
+            return Ok(());
+        }
+
+        let mut merged = format!("{}\n\n", lib.trim_end());
+
+        for line in missing {
+
+            merged.push_str(line);
+
+            merged.push('\n');
+        }
+
+        output.write(file_path, merged)
+    }
+
+    /// Removes the files of operations no longer in the spec.
+    fn remove_stale_methods(
+        &self,
+        output: &mut impl Output,
+        methods_mod_path: &Path,
+    ) -> std::io::Result<()> {
+
+        for file_path in output.list(methods_mod_path)? {
+
+            let Some(name) = file_path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(".rs"))
+            else {
+
+                continue;
+            };
+
+            if name != "mod" && !self.methods.contains_key(name) {
+
+                output.remove(&file_path)?;
+            }
+        }
 
         Ok(())
     }
+
+    fn cargo_toml(&self) -> String {
+
+        let mut manifest = CARGO_TOML.trim_end().to_string();
+
+        for dependency in &self.dependencies {
+
+            manifest.push('\n');
+
+            manifest.push_str(dependency.manifest_entry());
+        }
+
+        manifest.push('\n');
+
+        manifest
+    }
+
+    fn lib_rs(&self) -> String {
+
+        let mut lib = LIB_RS.to_string();
+
+        if self.dependencies.contains(&Dependency::Base64) {
+
+            lib.push_str("\nmod formats;\n\npub use formats::*;\n");
+        }
+
+        lib
+    }
+}
+
+/// Whether the `lib.rs` line `kept` already provides the template `line`,
+/// including with a different visibility, such as `pub mod types;`.
+fn declares(kept: &str, line: &str) -> bool {
+
+    let kept = kept.trim();
+
+    kept == line || kept.ends_with(&format!(" {line}"))
+}
+
+/// Writes `contents` unless the file exists, to preserve user edits.
+fn write_scaffolding(output: &mut impl Output, path: &str, contents: &str) -> std::io::Result<()> {
+
+    let path = Path::new(path);
+
+    if output.read(path)?.is_some() {
+
+        return Ok(());
+    }
+
+    output.write(path, contents.to_string())
 }
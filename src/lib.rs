@@ -1,10 +1,12 @@
 mod args;
+mod error;
 mod generator;
 #[cfg(test)]
 mod test;
 
-use args::Args;
+use args::{Args, Command, SpecArgs};
 use clap::Parser;
+pub use error::*;
 pub use generator::*;
 
 pub fn run() {
@@ -13,27 +15,115 @@ pub fn run() {
 
     args.init_tracing();
 
-    let generator = OpenAPIGenerator::from_json(
-        &std::fs::read_to_string(&args.json_path).expect("Failed to read OpenAPI file"),
-    );
+    let result = match (&args.command, &args.spec) {
+        (Some(Command::Check(spec)), _) => check(spec),
+        (None, Some(spec)) => generate(&args, spec),
+        (None, None) => unreachable!("clap requires the spec path without a subcommand"),
+    };
 
-    tracing::info!("Generating code...");
+    if let Err(e) = result {
+
+        tracing::error!("{e}");
+
+        std::process::exit(1);
+    }
+}
+
+fn builder(spec: &SpecArgs) -> Result<GeneratorBuilder> {
 
-    let methods = generator.gen_methods();
+    let mut builder = Generator::builder().spec(&spec.spec_path);
+
+    if let Some(type_mapping) = &spec.type_mapping {
+
+        let data = std::fs::read_to_string(type_mapping).map_err(|source| Error::Read {
+            path: type_mapping.clone(),
+            source,
+        })?;
+
+        builder = builder.type_mapping(TypeMapping::from_toml(&data)?);
+    }
+
+    Ok(builder)
+}
 
-    let structs = generator.gen_types();
+/// Prints the diff of the files that are not up to date, and fails if any.
+fn check(spec: &SpecArgs) -> Result<()> {
+
+    let generated = builder(spec)?.generate()?;
+
+    let diffs = generated.check(spec.output_path())?;
+
+    for diff in &diffs {
+
+        print!("{}", diff.diff);
+    }
+
+    report_skipped(spec, generated.diagnostics)?;
+
+    if !diffs.is_empty() {
+
+        return Err(Error::OutOfDate(diffs));
+    }
+
+    tracing::info!("Generated code is up to date");
+
+    Ok(())
+}
+
+fn generate(args: &Args, spec: &SpecArgs) -> Result<()> {
+
+    tracing::info!("Generating code...");
+
+    let generated = builder(spec)?.generate()?;
 
     tracing::info!("Code generation completed");
 
-    let output_path = args.output_path();
+    let output_path = spec.output_path();
+
+    generated.write(output_path)?;
 
-    CrateWriter::new(output_path, structs, methods)
-        .write()
-        .expect("Failed to write crate");
+    if args.clippy {
 
-    Formatter::new(output_path).format();
+        Formatter::new(output_path).format()?;
+    }
 
     tracing::warn!(
-        "Code generation completed successfully. You should write your Client manually, refer to the src/client.rs"
+        "Code generation completed successfully. You should write your Client manually, refer to the src/client.rs, it is kept on regeneration"
     );
+
+    if !generated.renames.is_empty() {
+
+        tracing::warn!("Renamed {} clashing names:", generated.renames.len());
+
+        for rename in &generated.renames {
+
+            tracing::warn!("  {rename}");
+        }
+    }
+
+    report_skipped(spec, generated.diagnostics)
+}
+
+/// Lists the skipped operations and schemas, which fail the run in strict
+/// mode.
+fn report_skipped(spec: &SpecArgs, diagnostics: Vec<Diagnostic>) -> Result<()> {
+
+    if diagnostics.is_empty() {
+
+        return Ok(());
+    }
+
+    tracing::warn!("Skipped {} operations or schemas:", diagnostics.len());
+
+    for diagnostic in &diagnostics {
+
+        tracing::warn!("  {diagnostic}");
+    }
+
+    if spec.strict {
+
+        return Err(Error::Skipped(diagnostics));
+    }
+
+    Ok(())
 }
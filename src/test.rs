@@ -6,13 +6,614 @@ fn test_emby() {
 
     let data = include_str!("../tests/openapi.json");
 
-    let generator = OpenAPIGenerator::from_json(data);
+    let generator = OpenAPIGenerator::from_json(data).expect("Failed to parse spec");
 
     let methods = generator.gen_methods();
 
     let structs = generator.gen_types();
 
     CrateWriter::new("./client", structs, methods)
+        .with_dependencies(generator.dependencies())
         .write()
         .expect("Failed to write crate");
 }
+
+#[test]
+
+fn test_string_formats() {
+
+    let spec = r#"{
+        "openapi": "3.0.3",
+        "info": { "title": "t", "version": "1" },
+        "paths": {},
+        "components": {
+            "schemas": {
+                "Event": {
+                    "type": "object",
+                    "required": ["id", "created", "day", "payload", "blob", "link", "name"],
+                    "properties": {
+                        "id": { "type": "string", "format": "uuid" },
+                        "created": { "type": "string", "format": "date-time" },
+                        "day": { "type": "string", "format": "date" },
+                        "payload": { "type": "string", "format": "byte" },
+                        "blob": { "type": "string", "format": "binary" },
+                        "link": { "type": "string", "format": "uri" },
+                        "name": { "type": "string", "format": "email" }
+                    }
+                }
+            }
+        }
+    }"#;
+
+    let generator = OpenAPIGenerator::from_json(spec).expect("Failed to parse spec");
+
+    let types = generator.gen_types().to_string();
+
+    assert!(types.contains(
+        "pub id : uuid :: Uuid , \
+         pub created : chrono :: DateTime < chrono :: Utc > , \
+         pub day : chrono :: NaiveDate , \
+         pub payload : Base64 , \
+         pub blob : bytes :: Bytes , \
+         pub link : url :: Url , \
+         pub name : String ,"
+    ));
+
+    let output_dir = std::env::temp_dir().join("falocon-string-formats");
+
+    let _ = std::fs::remove_dir_all(&output_dir);
+
+    CrateWriter::new(
+        output_dir.to_str().unwrap(),
+        generator.gen_types(),
+        generator.gen_methods(),
+    )
+    .with_dependencies(generator.dependencies())
+    .write()
+    .expect("Failed to write crate");
+
+    let manifest = std::fs::read_to_string(output_dir.join("Cargo.toml")).unwrap();
+
+    for entry in [
+        r#"base64 = "0.22""#,
+        r#"bytes = { version = "1", features = ["serde"] }"#,
+        r#"chrono = { version = "0.4", features = ["serde"] }"#,
+        r#"url = { version = "2", features = ["serde"] }"#,
+        r#"uuid = { version = "1", features = ["serde"] }"#,
+    ] {
+
+        assert!(manifest.contains(entry), "{entry} missing from {manifest}");
+    }
+
+    assert!(output_dir.join("src/formats.rs").exists());
+
+    std::fs::remove_dir_all(&output_dir).unwrap();
+}
+
+#[test]
+
+fn test_deterministic_output() {
+
+    let data = include_str!("../tests/openapi.json");
+
+    let outputs = ["a", "b"].map(|run| {
+
+        let output_dir = std::env::temp_dir().join(format!("falocon-deterministic-{run}"));
+
+        let _ = std::fs::remove_dir_all(&output_dir);
+
+        Generator::builder()
+            .spec_data(data)
+            .generate()
+            .expect("Failed to generate")
+            .write(&output_dir)
+            .expect("Failed to write crate");
+
+        let files = read_files(&output_dir);
+
+        std::fs::remove_dir_all(&output_dir).unwrap();
+
+        files
+    });
+
+    assert!(!outputs[0].is_empty());
+
+    assert!(
+        outputs[0] == outputs[1],
+        "Generated output differs between runs"
+    );
+}
+
+/// Contents of every file under `dir`, by path relative to it.
+fn read_files(dir: &std::path::Path) -> std::collections::BTreeMap<std::path::PathBuf, Vec<u8>> {
+
+    let mut files = std::collections::BTreeMap::new();
+
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+
+        for entry in std::fs::read_dir(&current).unwrap() {
+
+            let path = entry.unwrap().path();
+
+            if path.is_dir() {
+
+                pending.push(path);
+            } else {
+
+                let relative = path.strip_prefix(dir).unwrap().to_path_buf();
+
+                files.insert(relative, std::fs::read(&path).unwrap());
+            }
+        }
+    }
+
+    files
+}
+
+#[test]
+
+fn test_regenerate_keeps_scaffolding() {
+
+    let spec = r#"{
+        "openapi": "3.0.3",
+        "info": { "title": "t", "version": "1" },
+        "paths": {
+            "/pets": { "get": { "operationId": "listPets", "responses": {} } },
+            "/files": {
+                "get": {
+                    "operationId": "getFile",
+                    "responses": {
+                        "200": {
+                            "description": "ok",
+                            "content": {
+                                "application/json": { "schema": { "type": "string", "format": "byte" } }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }"#;
+
+    let output_dir = std::env::temp_dir().join("falocon-regenerate");
+
+    let _ = std::fs::remove_dir_all(&output_dir);
+
+    std::fs::create_dir_all(output_dir.join("src")).unwrap();
+
+    std::fs::write(
+        output_dir.join("Cargo.toml"),
+        "[dependencies]\nserde = \"1\"\n",
+    )
+    .unwrap();
+
+    std::fs::write(output_dir.join("src/client.rs"), "// my client\n").unwrap();
+
+    std::fs::create_dir_all(output_dir.join("src/methods")).unwrap();
+
+    std::fs::write(output_dir.join("src/methods/removed_op.rs"), "").unwrap();
+
+    Generator::builder()
+        .spec_data(spec)
+        .generate()
+        .expect("Failed to generate")
+        .write(&output_dir)
+        .expect("Failed to write crate");
+
+    let read = |file: &str| std::fs::read_to_string(output_dir.join(file)).unwrap();
+
+    assert_eq!(read("src/client.rs"), "// my client\n");
+
+    assert_eq!(
+        read("Cargo.toml"),
+        "[dependencies]\nbase64 = \"0.22\"\nserde = \"1\"\n"
+    );
+
+    assert!(read("src/lib.rs").contains("mod formats;"));
+
+    assert!(output_dir.join("src/methods/list_pets.rs").exists());
+
+    assert!(!output_dir.join("src/methods/removed_op.rs").exists());
+
+    std::fs::remove_dir_all(&output_dir).unwrap();
+}
+
+#[test]
+
+fn test_regenerate_previous_template() {
+
+    let spec = r#"{
+        "openapi": "3.0.3",
+        "info": { "title": "t", "version": "1" },
+        "paths": { "/pets": { "get": { "operationId": "listPets", "responses": {} } } }
+    }"#;
+
+    let output_dir = std::env::temp_dir().join("falocon-regenerate-previous");
+
+    let _ = std::fs::remove_dir_all(&output_dir);
+
+    std::fs::create_dir_all(output_dir.join("src")).unwrap();
+
+    // lib.rs and error.rs as written by the template before responses and
+    // error bodies were generated
+    std::fs::write(
+        output_dir.join("src/lib.rs"),
+        "mod client;\nmod error;\nmod request;\nmod route;\nmod types;\nmod methods;\n\n\
+         pub use client::*;\npub use error::Error;\npub use request::Request;\n\
+         pub use route::*;\npub use types::*;\npub use methods::*;\n\n\
+         type Result<T> = std::result::Result<T, Error>;\n",
+    )
+    .unwrap();
+
+    std::fs::write(
+        output_dir.join("src/error.rs"),
+        "#[derive(thiserror::Error, Debug)]\npub enum Error {\n    #[error(\"http: {0}\")]\n    HttpError(reqwest::Error),\n}\n",
+    )
+    .unwrap();
+
+    Generator::builder()
+        .spec_data(spec)
+        .generate()
+        .expect("Failed to generate")
+        .write(&output_dir)
+        .expect("Failed to write crate");
+
+    let read = |file: &str| std::fs::read_to_string(output_dir.join(file)).unwrap();
+
+    let lib = read("src/lib.rs");
+
+    for line in include_str!("../template/src/lib.rs").lines() {
+
+        assert!(lib.lines().any(|kept| kept == line), "{line} missing");
+    }
+
+    assert!(lib.contains("type Result<T> = std::result::Result<T, Error>;"));
+
+    assert_eq!(
+        read("src/error.rs"),
+        include_str!("../template/src/error.rs")
+    );
+
+    for file in ["src/lib.rs", "src/error.rs", "src/route.rs"] {
+
+        syn::parse_file(&read(file)).unwrap();
+    }
+
+    std::fs::remove_dir_all(&output_dir).unwrap();
+}
+
+#[test]
+
+fn test_check() {
+
+    let spec = r#"{
+        "openapi": "3.0.3",
+        "info": { "title": "t", "version": "1" },
+        "paths": { "/pets": { "get": { "operationId": "listPets", "responses": {} } } }
+    }"#;
+
+    let output_dir = std::env::temp_dir().join("falocon-check");
+
+    let _ = std::fs::remove_dir_all(&output_dir);
+
+    let generated = Generator::builder()
+        .spec_data(spec)
+        .generate()
+        .expect("Failed to generate");
+
+    assert_eq!(generated.check(&output_dir).unwrap().len(), 11);
+
+    assert!(!output_dir.exists());
+
+    generated.write(&output_dir).expect("Failed to write crate");
+
+    assert!(generated.check(&output_dir).unwrap().is_empty());
+
+    let stale = output_dir.join("src/methods/removed_op.rs");
+
+    std::fs::write(&stale, "").unwrap();
+
+    let diffs = generated.check(&output_dir).unwrap();
+
+    assert_eq!(diffs.len(), 1);
+
+    assert_eq!(
+        diffs[0].path,
+        std::path::Path::new("src/methods/removed_op.rs")
+    );
+
+    assert!(
+        diffs[0]
+            .diff
+            .starts_with("--- a/src/methods/removed_op.rs\n+++ /dev/null\n")
+    );
+
+    assert!(stale.exists());
+
+    std::fs::remove_dir_all(&output_dir).unwrap();
+}
+
+#[test]
+
+fn test_module() {
+
+    let spec = r#"{
+        "openapi": "3.0.3",
+        "info": { "title": "t", "version": "1" },
+        "paths": { "/pets": { "get": { "operationId": "listPets", "responses": {} } } },
+        "components": { "schemas": { "File": { "type": "string", "format": "byte" } } }
+    }"#;
+
+    let module = Generator::builder()
+        .spec_data(spec)
+        .generate()
+        .expect("Failed to generate")
+        .module()
+        .expect("Failed to inline modules");
+
+    syn::parse_file(&module).expect("Module is not valid Rust");
+
+    assert!(module.contains("pub mod list_pets {"));
+
+    assert!(module.contains("mod formats {"));
+
+    assert!(!module.contains("crate::"));
+
+    assert!(
+        !module
+            .lines()
+            .any(|line| line.trim().ends_with("mod types;"))
+    );
+}
+
+#[test]
+
+fn test_sources() {
+
+    let generated = Generator::builder()
+        .spec("tests/multi-file/openapi.yaml")
+        .generate()
+        .expect("Failed to generate");
+
+    let sources: Vec<String> = generated
+        .sources
+        .iter()
+        .map(|source| source.to_string_lossy().replace('\\', "/"))
+        .collect();
+
+    assert_eq!(sources.len(), 3);
+
+    assert_eq!(sources[0], "tests/multi-file/openapi.yaml");
+
+    assert!(sources[1].ends_with("tests/multi-file/parameters.yaml"));
+
+    assert!(sources[2].ends_with("tests/multi-file/schemas/user.yaml"));
+}
+
+#[test]
+
+fn test_emit_module_without_out_dir() {
+
+    // cargo only sets OUT_DIR for crates with a build script
+    assert!(std::env::var_os("OUT_DIR").is_none());
+
+    let result = Builder::new("tests/discriminator.yaml").emit_module();
+
+    assert!(matches!(result, Err(Error::OutDir)));
+}
+
+#[test]
+
+fn test_discriminator_fixture() {
+
+    let generated = Generator::builder()
+        .spec("tests/discriminator.yaml")
+        .generate()
+        .expect("Failed to generate")
+        .module()
+        .expect("Failed to inline modules");
+
+    let fixture = "tests/generated/discriminator.rs";
+
+    // regenerate with FALOCON_BLESS=1 after changing the generator
+    if std::env::var_os("FALOCON_BLESS").is_some() {
+
+        std::fs::write(fixture, &generated).unwrap();
+    }
+
+    assert_eq!(generated, std::fs::read_to_string(fixture).unwrap());
+}
+
+#[test]
+
+fn test_swagger2_form_data() {
+
+    let spec = r#"{
+        "swagger": "2.0",
+        "info": { "title": "t", "version": "1" },
+        "paths": {
+            "/pet/{petId}": {
+                "post": {
+                    "operationId": "updatePetWithForm",
+                    "consumes": ["application/x-www-form-urlencoded"],
+                    "parameters": [
+                        { "in": "path", "name": "petId", "required": true, "type": "integer" },
+                        { "in": "formData", "name": "name", "type": "string" }
+                    ],
+                    "responses": { "200": { "description": "ok" } }
+                }
+            },
+            "/pet/{petId}/uploadImage": {
+                "post": {
+                    "operationId": "uploadFile",
+                    "consumes": ["multipart/form-data"],
+                    "parameters": [
+                        { "in": "path", "name": "petId", "required": true, "type": "integer" },
+                        { "in": "formData", "name": "file", "type": "file" }
+                    ],
+                    "responses": { "200": { "description": "ok" } }
+                }
+            }
+        }
+    }"#;
+
+    let generated = Generator::builder()
+        .spec_data(spec)
+        .generate()
+        .expect("Failed to generate");
+
+    let update = format_tokens(&generated.methods["update_pet_with_form"]);
+
+    assert!(update.contains("pub body: UpdatePetWithFormBody,"));
+
+    assert!(generated.renames.is_empty());
+
+    assert!(update.contains("const FORM: bool = true;"));
+
+    assert_eq!(
+        generated.diagnostics,
+        vec![Diagnostic {
+            pointer: "/paths/~1pet~1{petId}~1uploadImage/post".to_string(),
+            reason: "Unsupported request body media type multipart/form-data".to_string(),
+        }]
+    );
+
+    assert!(!generated.methods.contains_key("upload_file"));
+
+    assert!(
+        Generator::builder()
+            .spec_data(spec)
+            .strict(true)
+            .generate()
+            .is_err()
+    );
+}
+
+#[test]
+
+fn test_invalid_identifiers() {
+
+    let spec = r#"{
+        "openapi": "3.0.3",
+        "info": { "title": "t", "version": "1" },
+        "paths": {
+            "/users/{user-id}": {
+                "get": {
+                    "operationId": "2fa",
+                    "parameters": [
+                        { "in": "path", "name": "user-id", "required": true, "schema": { "type": "string" } },
+                        { "in": "query", "name": "1st", "schema": { "type": "string" } }
+                    ],
+                    "responses": {}
+                }
+            },
+            "/crate": { "get": { "operationId": "crate", "responses": {} } },
+            "/type": { "get": { "operationId": "type", "responses": {} } }
+        },
+        "components": {
+            "schemas": {
+                "2fa": {
+                    "type": "object",
+                    "properties": { "3d": { "type": "string" }, "self": { "type": "string" } }
+                },
+                "Digit": { "type": "string", "enum": ["1", "2", ""] }
+            }
+        }
+    }"#;
+
+    let generated = Generator::builder()
+        .spec_data(spec)
+        .generate()
+        .expect("Failed to generate");
+
+    let op = format_tokens(&generated.methods["op2fa"]);
+
+    assert!(op.contains("pub struct Op2fa {\n    pub user_id: String,"));
+
+    assert!(op.contains(r#"Self::PATH.replace("{user-id}", &self.user_id.to_string())"#));
+
+    assert!(op.contains("pub _1st: String,"));
+
+    assert!(generated.methods.contains_key("op_crate"));
+
+    assert!(generated.methods.contains_key("type"));
+
+    let types = format_tokens(&generated.types);
+
+    assert!(types.contains("pub struct Schema2fa {"));
+
+    assert!(types.contains("#[serde(rename = \"3d\")]\n    pub _3d: Option<String>,"));
+
+    assert!(types.contains("#[serde(rename = \"self\")]\n    pub self_: Option<String>,"));
+
+    assert!(types.contains("    _1,\n"));
+
+    assert!(types.contains("    empty,\n"));
+
+    let module = generated.module().expect("Failed to inline modules");
+
+    assert!(module.contains("pub mod r#type {"));
+
+    syn::parse_file(&module).expect("Module is not valid Rust");
+}
+
+/// The runtime files of the template, compiled on their own to test them.
+#[allow(dead_code, clippy::all)]
+mod template {
+
+    mod client {
+
+        include!("../template/src/client.rs");
+    }
+
+    mod error {
+
+        include!("../template/src/error.rs");
+    }
+
+    mod request {
+
+        include!("../template/src/request.rs");
+    }
+
+    mod response {
+
+        include!("../template/src/response.rs");
+    }
+
+    mod route {
+
+        include!("../template/src/route.rs");
+    }
+
+    pub use client::*;
+    pub use error::Error;
+    pub use request::Request;
+    pub use response::FromResponse;
+    pub use route::*;
+
+    #[test]
+
+    fn test_cookie_header() {
+
+        #[derive(serde::Serialize)]
+        struct Cookies {
+            session: &'static str,
+            theme: Option<&'static str>,
+            count: i32,
+        }
+
+        let cookies = Cookies {
+            session: "a b;c,d%é",
+            theme: None,
+            count: 2,
+        };
+
+        assert_eq!(
+            route::cookie_header::<_, ()>(&cookies).unwrap(),
+            "session=a%20b%3Bc%2Cd%25%C3%A9; count=2"
+        );
+    }
+}
@@ -1,6 +1,24 @@
 #[derive(thiserror::Error, Debug)]
 
-pub enum Error {
+pub enum Error<E = ()> {
     #[error("http: {0}")]
     HttpError(reqwest::Error),
+    #[error("serialize: {0}")]
+    SerializeError(serde_json::Error),
+    #[error("deserialize: {0}")]
+    DeserializeError(serde_json::Error),
+    #[error("invalid header: {0}")]
+    InvalidHeader(String),
+    #[error("unexpected status: {0}")]
+    UnexpectedStatus(reqwest::StatusCode),
+    /// A 4xx or 5xx response. `error` holds the body decoded into the
+    /// operation's error type, when the spec declares one for `status`.
+    #[error("status {status}: {body}")]
+    Status {
+        status: reqwest::StatusCode,
+        body: String,
+        error: Option<E>,
+    },
 }
+
+pub(crate) type Result<T, E = ()> = std::result::Result<T, Error<E>>;
@@ -1,6 +1,7 @@
 mod client;
 mod error;
 mod request;
+mod response;
 mod route;
 mod types;
 mod methods;
@@ -8,8 +9,7 @@ mod methods;
 pub use client::*;
 pub use error::Error;
 pub use request::Request;
+pub use response::FromResponse;
 pub use route::*;
 pub use types::*;
 pub use methods::*;
-
-type Result<T> = std::result::Result<T, Error>;
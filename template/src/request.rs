@@ -2,17 +2,30 @@ use std::borrow::Cow;
 
 use reqwest::Method;
 
+use super::FromResponse;
+
 pub trait Request: Sized + Send + 'static {
-    type Response: serde::de::DeserializeOwned + Send + 'static;
+    type Response: FromResponse + Send + 'static;
+
+    /// Error bodies declared for the 4xx and 5xx responses.
+    type Error: FromResponse + Send + 'static;
 
     type Body: serde::ser::Serialize + Send + 'static;
 
     type Params: serde::ser::Serialize + Send + 'static;
 
+    type Headers: serde::ser::Serialize + Send + 'static;
+
+    type Cookies: serde::ser::Serialize + Send + 'static;
+
     const METHOD: Method;
 
     const PATH: &'static str;
 
+    /// Whether the body is sent as `application/x-www-form-urlencoded`
+    /// instead of JSON.
+    const FORM: bool = false;
+
     fn body(&self) -> Option<&Self::Body> {
         None
     }
@@ -21,6 +34,17 @@ pub trait Request: Sized + Send + 'static {
         None
     }
 
+    /// Header parameters of the operation, merged on top of
+    /// `ClientPrelude::headers`.
+    fn headers(&self) -> Option<&Self::Headers> {
+        None
+    }
+
+    /// Cookie parameters of the operation, sent as a single `Cookie` header.
+    fn cookies(&self) -> Option<&Self::Cookies> {
+        None
+    }
+
     fn path(&self) -> Cow<'static, str> {
         Cow::Borrowed(Self::PATH)
     }
@@ -0,0 +1,7 @@
+use reqwest::StatusCode;
+
+/// Decodes a response body into the variant declared for its status code.
+pub trait FromResponse: Sized {
+    /// Returns `None` when the operation declares nothing for `status`.
+    fn from_response(status: StatusCode, body: &[u8]) -> Option<serde_json::Result<Self>>;
+}
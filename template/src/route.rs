@@ -1,6 +1,8 @@
 use futures::{FutureExt, future::BoxFuture};
+use reqwest::header::{COOKIE, HeaderMap, HeaderName, HeaderValue};
 
 use super::*;
+use super::error::Result;
 
 pub struct Route<C, K> {
     client: C,
@@ -30,7 +32,7 @@ where
     C: ClientPrelude,
     Re: Request,
 {
-    type Output = Result<Re::Response>;
+    type Output = Result<Re::Response, Re::Error>;
 
     type IntoFuture = BoxFuture<'static, Self::Output>;
 
@@ -41,12 +43,38 @@ where
                 .client()
                 .request(Re::METHOD, format!("{}{}", C::BASE_URI, self.kind.path()));
 
-            if let Some(headers) = self.client.headers() {
+            let mut headers = self.client.headers().unwrap_or_default();
+
+            if let Some(request_headers) = self.kind.headers() {
+                headers.extend(header_map(request_headers)?);
+            }
+
+            if let Some(cookies) = self.kind.cookies() {
+                let cookie = cookie_header(cookies)?;
+
+                if !cookie.is_empty() {
+                    let cookie = match headers.get(COOKIE).and_then(|v| v.to_str().ok()) {
+                        Some(existing) => format!("{existing}; {cookie}"),
+                        None => cookie,
+                    };
+
+                    let value = HeaderValue::from_str(&cookie)
+                        .map_err(|e| Error::InvalidHeader(e.to_string()))?;
+
+                    headers.insert(COOKIE, value);
+                }
+            }
+
+            if !headers.is_empty() {
                 request = request.headers(headers)
             }
 
             if let Some(body) = self.kind.body() {
-                request = request.json(&body);
+                request = if Re::FORM {
+                    request.form(&fields(body)?)
+                } else {
+                    request.json(&body)
+                };
             }
 
             if let Some(params) = self.kind.params() {
@@ -55,13 +83,88 @@ where
 
             let response = request.send().await.map_err(Error::HttpError)?;
 
-            let data = response
-                .json::<Re::Response>()
-                .await
-                .map_err(Error::HttpError)?;
+            let status = response.status();
 
-            Ok(data)
+            let body = response.bytes().await.map_err(Error::HttpError)?;
+
+            if status.is_client_error() || status.is_server_error() {
+                let error = Re::Error::from_response(status, &body).and_then(|error| error.ok());
+
+                return Err(Error::Status {
+                    status,
+                    body: String::from_utf8_lossy(&body).into_owned(),
+                    error,
+                });
+            }
+
+            match Re::Response::from_response(status, &body) {
+                Some(data) => data.map_err(Error::DeserializeError),
+                None => Err(Error::UnexpectedStatus(status)),
+            }
         }
         .boxed()
     }
 }
+
+/// Flattens a serialized parameters struct into `(name, value)` pairs,
+/// skipping unset fields.
+fn fields<T: serde::Serialize, E>(value: &T) -> Result<Vec<(String, String)>, E> {
+    let serde_json::Value::Object(fields) =
+        serde_json::to_value(value).map_err(Error::SerializeError)?
+    else {
+        return Ok(Vec::new());
+    };
+
+    Ok(fields
+        .into_iter()
+        .filter_map(|(name, value)| match value {
+            serde_json::Value::Null => None,
+            serde_json::Value::String(value) => Some((name, value)),
+            value => Some((name, value.to_string())),
+        })
+        .collect())
+}
+
+fn header_map<T: serde::Serialize, E>(headers: &T) -> Result<HeaderMap, E> {
+    let mut map = HeaderMap::new();
+
+    for (name, value) in fields(headers)? {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| Error::InvalidHeader(e.to_string()))?;
+
+        let value =
+            HeaderValue::from_str(&value).map_err(|e| Error::InvalidHeader(e.to_string()))?;
+
+        map.insert(name, value);
+    }
+
+    Ok(map)
+}
+
+/// The `Cookie` header value of a serialized cookies struct, with the values
+/// percent-encoded.
+pub(crate) fn cookie_header<T: serde::Serialize, E>(cookies: &T) -> Result<String, E> {
+    Ok(fields(cookies)?
+        .into_iter()
+        .map(|(name, value)| format!("{name}={}", encode_cookie_value(&value)))
+        .collect::<Vec<_>>()
+        .join("; "))
+}
+
+/// Percent-encodes the bytes a cookie value cannot hold, such as `;`, `,`,
+/// spaces and non ASCII characters, see RFC 6265.
+fn encode_cookie_value(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+
+    for byte in value.bytes() {
+        match byte {
+            b'%' => encoded.push_str("%25"),
+            b'!' | b'#'..=b'+' | b'-'..=b':' | b'<'..=b'[' | b']'..=b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+
+    encoded
+}
@@ -0,0 +1,68 @@
+#[allow(unused_imports, dead_code, clippy::all)]
+mod generated {
+
+    include!("generated/discriminator.rs");
+}
+
+use generated::{Pet, Shape};
+
+#[test]
+
+fn test_tag_in_variants_round_trip() {
+
+    let json = serde_json::json!({ "petType": "dog", "name": "Rex", "barks": true });
+
+    let pet: Pet = serde_json::from_value(json.clone()).unwrap();
+
+    let Pet::Dog(dog) = &pet else {
+
+        panic!("expected a dog, got {pet:?}");
+    };
+
+    assert_eq!(dog.pet_base.pet_type, "dog");
+
+    assert_eq!(serde_json::to_value(&pet).unwrap(), json);
+
+    let cat: Pet =
+        serde_json::from_value(serde_json::json!({ "petType": "cat", "name": "Tom", "lives": 9 }))
+            .unwrap();
+
+    assert!(matches!(cat, Pet::Cat(_)));
+
+    assert!(serde_json::from_value::<Pet>(serde_json::json!({ "name": "Rex" })).is_err());
+}
+
+#[test]
+
+fn test_inline_variants_round_trip() {
+
+    let json = serde_json::json!({ "petType": "fish", "name": "Nemo" });
+
+    let fish: Pet = serde_json::from_value(json.clone()).unwrap();
+
+    assert!(matches!(fish, Pet::Fish(_)));
+
+    assert_eq!(serde_json::to_value(&fish).unwrap(), json);
+
+    // no tag values listed, tried after the known ones
+    let json = serde_json::json!({ "petType": "parrot", "wings": 2 });
+
+    let bird: Pet = serde_json::from_value(json.clone()).unwrap();
+
+    assert!(matches!(bird, Pet::Bird(_)));
+
+    assert_eq!(serde_json::to_value(&bird).unwrap(), json);
+}
+
+#[test]
+
+fn test_internally_tagged_round_trip() {
+
+    let json = serde_json::json!({ "kind": "Circle", "radius": 1.5 });
+
+    let shape: Shape = serde_json::from_value(json.clone()).unwrap();
+
+    assert!(matches!(shape, Shape::Circle(_)));
+
+    assert_eq!(serde_json::to_value(&shape).unwrap(), json);
+}
@@ -0,0 +1,380 @@
+mod client {
+use super::{Request, Route};
+
+pub trait ClientPrelude: Clone + Send + 'static {
+    const BASE_URI: &'static str;
+
+    fn headers(&self) -> Option<reqwest::header::HeaderMap>;
+
+    fn client(&self) -> reqwest::Client;
+
+    fn route<T>(&self, kind: T) -> Route<Self, T>
+    where
+        T: Request,
+    {
+        Route::new(self, kind)
+    }
+}
+}
+mod error {
+#[derive(thiserror::Error, Debug)]
+
+pub enum Error<E = ()> {
+    #[error("http: {0}")]
+    HttpError(reqwest::Error),
+    #[error("serialize: {0}")]
+    SerializeError(serde_json::Error),
+    #[error("deserialize: {0}")]
+    DeserializeError(serde_json::Error),
+    #[error("invalid header: {0}")]
+    InvalidHeader(String),
+    #[error("unexpected status: {0}")]
+    UnexpectedStatus(reqwest::StatusCode),
+    /// A 4xx or 5xx response. `error` holds the body decoded into the
+    /// operation's error type, when the spec declares one for `status`.
+    #[error("status {status}: {body}")]
+    Status {
+        status: reqwest::StatusCode,
+        body: String,
+        error: Option<E>,
+    },
+}
+
+pub(crate) type Result<T, E = ()> = std::result::Result<T, Error<E>>;
+}
+mod request {
+use std::borrow::Cow;
+
+use reqwest::Method;
+
+use super::FromResponse;
+
+pub trait Request: Sized + Send + 'static {
+    type Response: FromResponse + Send + 'static;
+
+    /// Error bodies declared for the 4xx and 5xx responses.
+    type Error: FromResponse + Send + 'static;
+
+    type Body: serde::ser::Serialize + Send + 'static;
+
+    type Params: serde::ser::Serialize + Send + 'static;
+
+    type Headers: serde::ser::Serialize + Send + 'static;
+
+    type Cookies: serde::ser::Serialize + Send + 'static;
+
+    const METHOD: Method;
+
+    const PATH: &'static str;
+
+    /// Whether the body is sent as `application/x-www-form-urlencoded`
+    /// instead of JSON.
+    const FORM: bool = false;
+
+    fn body(&self) -> Option<&Self::Body> {
+        None
+    }
+
+    fn params(&self) -> Option<&Self::Params> {
+        None
+    }
+
+    /// Header parameters of the operation, merged on top of
+    /// `ClientPrelude::headers`.
+    fn headers(&self) -> Option<&Self::Headers> {
+        None
+    }
+
+    /// Cookie parameters of the operation, sent as a single `Cookie` header.
+    fn cookies(&self) -> Option<&Self::Cookies> {
+        None
+    }
+
+    fn path(&self) -> Cow<'static, str> {
+        Cow::Borrowed(Self::PATH)
+    }
+}
+}
+mod response {
+use reqwest::StatusCode;
+
+/// Decodes a response body into the variant declared for its status code.
+pub trait FromResponse: Sized {
+    /// Returns `None` when the operation declares nothing for `status`.
+    fn from_response(status: StatusCode, body: &[u8]) -> Option<serde_json::Result<Self>>;
+}
+}
+mod route {
+use futures::{FutureExt, future::BoxFuture};
+use reqwest::header::{COOKIE, HeaderMap, HeaderName, HeaderValue};
+
+use super::*;
+use super::error::Result;
+
+pub struct Route<C, K> {
+    client: C,
+    kind: K,
+}
+
+impl<C, K> Route<C, K>
+where
+    C: ClientPrelude,
+{
+    pub fn new(client: &C, kind: K) -> Self {
+        Self {
+            client: client.clone(),
+            kind,
+        }
+    }
+}
+
+impl<C, Re: Request> std::fmt::Display for Route<C, Re> {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt.write_str(&self.kind.path())
+    }
+}
+
+impl<C, Re> IntoFuture for Route<C, Re>
+where
+    C: ClientPrelude,
+    Re: Request,
+{
+    type Output = Result<Re::Response, Re::Error>;
+
+    type IntoFuture = BoxFuture<'static, Self::Output>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        async move {
+            let mut request = self
+                .client
+                .client()
+                .request(Re::METHOD, format!("{}{}", C::BASE_URI, self.kind.path()));
+
+            let mut headers = self.client.headers().unwrap_or_default();
+
+            if let Some(request_headers) = self.kind.headers() {
+                headers.extend(header_map(request_headers)?);
+            }
+
+            if let Some(cookies) = self.kind.cookies() {
+                let cookie = cookie_header(cookies)?;
+
+                if !cookie.is_empty() {
+                    let cookie = match headers.get(COOKIE).and_then(|v| v.to_str().ok()) {
+                        Some(existing) => format!("{existing}; {cookie}"),
+                        None => cookie,
+                    };
+
+                    let value = HeaderValue::from_str(&cookie)
+                        .map_err(|e| Error::InvalidHeader(e.to_string()))?;
+
+                    headers.insert(COOKIE, value);
+                }
+            }
+
+            if !headers.is_empty() {
+                request = request.headers(headers)
+            }
+
+            if let Some(body) = self.kind.body() {
+                request = if Re::FORM {
+                    request.form(&fields(body)?)
+                } else {
+                    request.json(&body)
+                };
+            }
+
+            if let Some(params) = self.kind.params() {
+                request = request.query(&params);
+            }
+
+            let response = request.send().await.map_err(Error::HttpError)?;
+
+            let status = response.status();
+
+            let body = response.bytes().await.map_err(Error::HttpError)?;
+
+            if status.is_client_error() || status.is_server_error() {
+                let error = Re::Error::from_response(status, &body).and_then(|error| error.ok());
+
+                return Err(Error::Status {
+                    status,
+                    body: String::from_utf8_lossy(&body).into_owned(),
+                    error,
+                });
+            }
+
+            match Re::Response::from_response(status, &body) {
+                Some(data) => data.map_err(Error::DeserializeError),
+                None => Err(Error::UnexpectedStatus(status)),
+            }
+        }
+        .boxed()
+    }
+}
+
+/// Flattens a serialized parameters struct into `(name, value)` pairs,
+/// skipping unset fields.
+fn fields<T: serde::Serialize, E>(value: &T) -> Result<Vec<(String, String)>, E> {
+    let serde_json::Value::Object(fields) =
+        serde_json::to_value(value).map_err(Error::SerializeError)?
+    else {
+        return Ok(Vec::new());
+    };
+
+    Ok(fields
+        .into_iter()
+        .filter_map(|(name, value)| match value {
+            serde_json::Value::Null => None,
+            serde_json::Value::String(value) => Some((name, value)),
+            value => Some((name, value.to_string())),
+        })
+        .collect())
+}
+
+fn header_map<T: serde::Serialize, E>(headers: &T) -> Result<HeaderMap, E> {
+    let mut map = HeaderMap::new();
+
+    for (name, value) in fields(headers)? {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| Error::InvalidHeader(e.to_string()))?;
+
+        let value =
+            HeaderValue::from_str(&value).map_err(|e| Error::InvalidHeader(e.to_string()))?;
+
+        map.insert(name, value);
+    }
+
+    Ok(map)
+}
+
+/// The `Cookie` header value of a serialized cookies struct, with the values
+/// percent-encoded.
+pub(crate) fn cookie_header<T: serde::Serialize, E>(cookies: &T) -> Result<String, E> {
+    Ok(fields(cookies)?
+        .into_iter()
+        .map(|(name, value)| format!("{name}={}", encode_cookie_value(&value)))
+        .collect::<Vec<_>>()
+        .join("; "))
+}
+
+/// Percent-encodes the bytes a cookie value cannot hold, such as `;`, `,`,
+/// spaces and non ASCII characters, see RFC 6265.
+fn encode_cookie_value(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+
+    for byte in value.bytes() {
+        match byte {
+            b'%' => encoded.push_str("%25"),
+            b'!' | b'#'..=b'+' | b'-'..=b':' | b'<'..=b'[' | b']'..=b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+
+    encoded
+}
+}
+mod types {
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PetBase {
+    #[serde(rename = "petType")]
+    pub pet_type: String,
+    pub name: String,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dog {
+    #[serde(flatten)]
+    pub pet_base: PetBase,
+    pub barks: Option<bool>,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cat {
+    #[serde(flatten)]
+    pub pet_base: PetBase,
+    pub lives: Option<i32>,
+}
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum Pet {
+    Dog(Dog),
+    Cat(Cat),
+    Fish(PetFish),
+    Bird(PetBird),
+}
+impl<'de> Deserialize<'de> for Pet {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        match value.get("petType").and_then(serde_json::Value::as_str) {
+            Some("dog") => {
+                serde_json::from_value(value)
+                    .map(Self::Dog)
+                    .map_err(serde::de::Error::custom)
+            }
+            Some("cat") => {
+                serde_json::from_value(value)
+                    .map(Self::Cat)
+                    .map_err(serde::de::Error::custom)
+            }
+            Some("fish") => {
+                serde_json::from_value(value)
+                    .map(Self::Fish)
+                    .map_err(serde::de::Error::custom)
+            }
+            Some(other) => {
+                if let Ok(variant) = serde_json::from_value(value.clone()) {
+                    return Ok(Self::Bird(variant));
+                }
+                Err(serde::de::Error::unknown_variant(other, &["dog", "cat", "fish"]))
+            }
+            None => Err(serde::de::Error::missing_field("petType")),
+        }
+    }
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PetFish {
+    #[serde(rename = "petType")]
+    pub pet_type: String,
+    pub name: String,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PetBird {
+    #[serde(rename = "petType")]
+    pub pet_type: String,
+    pub wings: i32,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Circle {
+    pub radius: f32,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Square {
+    pub side: f32,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum Shape {
+    #[serde(rename = "Circle")]
+    Circle(Circle),
+    #[serde(rename = "Square")]
+    Square(Square),
+}
+}
+mod methods {
+use super::types::*;
+use super::{FromResponse, Request};
+}
+
+pub use client::*;
+pub use error::Error;
+pub use request::Request;
+pub use response::FromResponse;
+pub use route::*;
+pub use types::*;
+pub use methods::*;